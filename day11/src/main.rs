@@ -2,16 +2,74 @@ use anyhow::{bail, Error};
 
 mod part1;
 mod part2;
+mod stats;
+mod stones;
+
+const USAGE: &str =
+    "Expected <filename> [--blinks <n>] [--rules <rules>] [--csv <path> [--top <k>]]";
 
 fn main() -> Result<(), Error> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        bail!("Expected <filename>");
+    let mut filename: Option<String> = None;
+    let mut blinks: Option<usize> = None;
+    let mut rules: Option<String> = None;
+    let mut csv: Option<String> = None;
+    let mut top = 5;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--blinks" | "--rules" | "--csv" | "--top" => {
+                let Some(value) = args.next() else {
+                    bail!("{} needs a value", arg);
+                };
+                match arg.as_str() {
+                    "--blinks" => blinks = Some(value.parse()?),
+                    "--rules" => rules = Some(value),
+                    "--csv" => csv = Some(value),
+                    _ => top = value.parse()?,
+                }
+            }
+            _ if filename.is_none() && !arg.starts_with("--") => filename = Some(arg),
+            _ => bail!(USAGE),
+        }
     }
-    let filename = &args[1];
+    let Some(filename) = filename else {
+        bail!(USAGE);
+    };
     let input = std::fs::read_to_string(filename)?;
-    println!("part1={}", part1::eval(&input)?);
-    println!("part2={}", part2::eval(&input)?);
+
+    if blinks.is_none() && rules.is_none() && csv.is_none() {
+        println!("part1={}", part1::eval(&input)?);
+        println!("part2={}", part2::eval(&input)?);
+        return Ok(());
+    }
+
+    // Custom runs: the puzzle's rules and 75 blinks unless told otherwise.
+    let rules = match rules {
+        Some(rules) => stones::Rules::parse(&rules)?,
+        None => stones::Rules::standard(),
+    };
+    let blinks = blinks.unwrap_or(75);
+    let stones = stones::parse(&input)?;
+
+    let Some(csv) = csv else {
+        let count = stones::count(&stones, &rules, blinks)?;
+        println!("blinks {}={}", blinks, count);
+        return Ok(());
+    };
+
+    let stats = stats::collect(&stones, &rules, blinks, top)?;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&csv)?);
+    stats::write_csv(&stats, &mut file)?;
+    let last = stats.last().unwrap();
+    println!("blinks {}={}", blinks, last.stones);
+    match stats::saturation(&stats) {
+        Some(blink) => println!(
+            "no new values from blink {} on ({} distinct values seen)",
+            blink,
+            stats.iter().map(|s| s.new_values).sum::<usize>()
+        ),
+        None => println!("still finding new values at blink {}", blinks),
+    }
 
     Ok(())
 }
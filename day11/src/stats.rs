@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use anyhow::{anyhow, Context, Error};
+
+use crate::stones::{self, Population, Rules, Stone};
+
+/// The population after some number of blinks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlinkStats {
+    pub blink: usize,
+    pub stones: u128,
+    /// Distinct values among the stones.
+    pub distinct: usize,
+    /// Distinct values never seen on any earlier blink.
+    pub new_values: usize,
+    /// The most common values and how many stones carry them, most common
+    /// first, ties broken by value.
+    pub top: Vec<(Stone, u128)>,
+}
+
+impl BlinkStats {
+    /// Fails if there are more stones in all than fit in a u128, even though
+    /// each value's count does.
+    fn new(
+        blink: usize,
+        pop: &Population,
+        seen: &mut HashSet<Stone>,
+        top: usize,
+    ) -> Result<BlinkStats, Error> {
+        let stones = pop
+            .values()
+            .try_fold(0u128, |sum, &n| sum.checked_add(n))
+            .ok_or_else(|| anyhow!("more than {} stones", u128::MAX))?;
+        let new_values = pop.keys().filter(|&&v| seen.insert(v)).count();
+        let mut common: Vec<(Stone, u128)> = pop.iter().map(|(&v, &n)| (v, n)).collect();
+        common.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        common.truncate(top);
+        Ok(BlinkStats {
+            blink,
+            stones,
+            distinct: pop.len(),
+            new_values,
+            top: common,
+        })
+    }
+}
+
+/// Statistics for the initial stones and after each of `blinks` blinks,
+/// keeping the `top` most common values of each.
+pub fn collect(
+    stones: &[Stone],
+    rules: &Rules,
+    blinks: usize,
+    top: usize,
+) -> Result<Vec<BlinkStats>, Error> {
+    let mut seen = HashSet::new();
+    let mut pop = stones::population(stones);
+    let mut stats = vec![BlinkStats::new(0, &pop, &mut seen, top)?];
+    for i in 1..=blinks {
+        pop = stones::blink(&pop, rules).with_context(|| format!("blink {}", i))?;
+        let blink_stats =
+            BlinkStats::new(i, &pop, &mut seen, top).with_context(|| format!("blink {}", i))?;
+        stats.push(blink_stats);
+    }
+    Ok(stats)
+}
+
+/// The first blink from which no new values appear through the end of
+/// `stats`, or None if the last blink still brought new ones.
+pub fn saturation(stats: &[BlinkStats]) -> Option<usize> {
+    let last_new = stats.iter().rposition(|s| s.new_values > 0)?;
+    stats.get(last_new + 1).map(|s| s.blink)
+}
+
+/// Writes one row per blink. The most common values go in the last column
+/// as space separated `value:count` pairs.
+pub fn write_csv(stats: &[BlinkStats], w: &mut impl Write) -> io::Result<()> {
+    writeln!(w, "blink,stones,distinct,new_values,top")?;
+    for s in stats {
+        let top: Vec<String> = s.top.iter().map(|(v, n)| format!("{}:{}", v, n)).collect();
+        writeln!(
+            w,
+            "{},{},{},{},{}",
+            s.blink,
+            s.stones,
+            s.distinct,
+            s.new_values,
+            top.join(" ")
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let stats = collect(&[125, 17], &Rules::standard(), 6, 2).unwrap();
+        // 125 17 -> 253000 1 7 -> 253 0 2024 14168 -> 512072 1 20 24 28676032
+        let counts: Vec<u128> = stats.iter().map(|s| s.stones).collect();
+        assert_eq!(counts, vec![2, 3, 4, 5, 9, 13, 22]);
+        assert_eq!(stats[3].distinct, 5);
+        assert_eq!(stats[6].top[0], (2, 4));
+
+        let mut csv = Vec::new();
+        write_csv(&stats[..2], &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "blink,stones,distinct,new_values,top\n0,2,2,2,17:1 125:1\n1,3,3,3,1:1 7:1\n"
+        );
+    }
+
+    #[test]
+    fn saturates() {
+        // 0 -> 1 -> 2024 -> 20 24 -> 2 0 2 4 -> ... only ever a few values.
+        let stats = collect(&[0], &Rules::standard(), 40, 3).unwrap();
+        let blink = saturation(&stats).unwrap();
+        assert!(stats[blink..].iter().all(|s| s.new_values == 0));
+        assert!(stats[blink - 1].new_values > 0);
+        assert_eq!(saturation(&stats[..3]), None);
+    }
+
+    #[test]
+    fn overflow() {
+        // After 256 blinks there are 2^127 stones of value 1 and as many of
+        // value 2: each count fits, their total doesn't.
+        let rules = Rules::parse("1 -> 12; 2 -> 12; 12 -> split").unwrap();
+        let stats = collect(&[1], &rules, 255, 1).unwrap();
+        assert_eq!(stats[254].stones, 1 << 127);
+        let err = collect(&[1], &rules, 256, 1).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            format!("blink 256: more than {} stones", u128::MAX)
+        );
+    }
+}
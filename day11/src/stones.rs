@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Error};
+
+/// A stone's value. Values grow by a factor of 2024 each time they're
+/// multiplied, so they get a u128, and every operation on them is checked.
+pub type Stone = u128;
+
+/// How many stones carry each value. The order of the stones never matters
+/// for counting them, so a blink only has to transform each distinct value
+/// once.
+pub type Population = HashMap<Stone, u128>;
+
+type Predicate = Box<dyn Fn(Stone) -> bool>;
+type Transform = Box<dyn Fn(Stone) -> Result<Vec<Stone>, Error>>;
+
+/// Replaces a stone matching `predicate` with the stones from `transform`.
+pub struct Rule {
+    predicate: Predicate,
+    transform: Transform,
+}
+
+impl Rule {
+    pub fn new(
+        predicate: impl Fn(Stone) -> bool + 'static,
+        transform: impl Fn(Stone) -> Result<Vec<Stone>, Error> + 'static,
+    ) -> Rule {
+        Rule {
+            predicate: Box::new(predicate),
+            transform: Box::new(transform),
+        }
+    }
+}
+
+/// Rules tried in order on each stone; the first that matches applies. A
+/// stone no rule matches is left as it is.
+pub struct Rules(Vec<Rule>);
+
+impl Rules {
+    pub fn new(rules: Vec<Rule>) -> Rules {
+        Rules(rules)
+    }
+
+    /// The puzzle's rules.
+    pub fn standard() -> Rules {
+        Rules::new(vec![
+            Rule::new(|v| v == 0, |_| Ok(vec![1])),
+            Rule::new(|v| l10(v).is_multiple_of(2), split),
+            Rule::new(|_| true, |v| Ok(vec![mul(v, 2024)?])),
+        ])
+    }
+
+    /// Parses rules written one per line or separated by ';', each as
+    /// `<predicate> -> <transform>`. Predicates are:
+    ///
+    /// - `N`: the stone is N
+    /// - `even-digits`, `odd-digits`: the number of digits is even or odd
+    /// - `%N`: the stone is a multiple of N
+    /// - `*`: any stone
+    ///
+    /// Transforms are:
+    ///
+    /// - `N`: replace with N
+    /// - `split`: replace with the left and right halves of the digits
+    /// - `*N`, `+N`: multiply or add N
+    ///
+    /// The standard rules are `0 -> 1; even-digits -> split; * -> *2024`.
+    pub fn parse(s: &str) -> Result<Rules, Error> {
+        let rules = s
+            .split(['\n', ';'])
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                let (pred, transform) = r
+                    .split_once("->")
+                    .ok_or_else(|| anyhow!("expected <predicate> -> <transform> in {:?}", r))?;
+                Ok(Rule {
+                    predicate: parse_predicate(pred.trim())?,
+                    transform: parse_transform(transform.trim())?,
+                })
+            })
+            .collect::<Result<Vec<Rule>, Error>>()?;
+        Ok(Rules(rules))
+    }
+
+    /// The stones `v` turns into, or an error if a value overflows.
+    pub fn apply(&self, v: Stone) -> Result<Vec<Stone>, Error> {
+        match self.0.iter().find(|r| (r.predicate)(v)) {
+            Some(rule) => (rule.transform)(v),
+            None => Ok(vec![v]),
+        }
+    }
+}
+
+fn parse_predicate(s: &str) -> Result<Predicate, Error> {
+    Ok(match s {
+        "*" => Box::new(|_| true),
+        "even-digits" => Box::new(|v| l10(v).is_multiple_of(2)),
+        "odd-digits" => Box::new(|v| !l10(v).is_multiple_of(2)),
+        _ => {
+            if let Some(n) = s.strip_prefix('%') {
+                let n: Stone = n.parse()?;
+                if n == 0 {
+                    bail!("%0 would divide by zero");
+                }
+                Box::new(move |v| v % n == 0)
+            } else {
+                let n: Stone = s
+                    .parse()
+                    .map_err(|_| anyhow!("unknown predicate {:?}", s))?;
+                Box::new(move |v| v == n)
+            }
+        }
+    })
+}
+
+fn parse_transform(s: &str) -> Result<Transform, Error> {
+    Ok(if s == "split" {
+        Box::new(split)
+    } else if let Some(n) = s.strip_prefix('*') {
+        let n: Stone = n.parse()?;
+        Box::new(move |v| Ok(vec![mul(v, n)?]))
+    } else if let Some(n) = s.strip_prefix('+') {
+        let n: Stone = n.parse()?;
+        Box::new(move |v| {
+            let sum = v
+                .checked_add(n)
+                .ok_or_else(|| anyhow!("{} + {} overflows", v, n))?;
+            Ok(vec![sum])
+        })
+    } else {
+        let n: Stone = s
+            .parse()
+            .map_err(|_| anyhow!("unknown transform {:?}", s))?;
+        Box::new(move |_| Ok(vec![n]))
+    })
+}
+
+pub fn parse(input: &str) -> Result<Vec<Stone>, Error> {
+    input
+        .split_whitespace()
+        .map(|s| {
+            s.parse::<Stone>()
+                .with_context(|| format!("invalid stone {:?}", s))
+        })
+        .collect()
+}
+
+pub fn population(stones: &[Stone]) -> Population {
+    let mut pop = Population::new();
+    for &v in stones {
+        *pop.entry(v).or_default() += 1;
+    }
+    pop
+}
+
+/// Applies one blink to every stone.
+pub fn blink(pop: &Population, rules: &Rules) -> Result<Population, Error> {
+    let mut next = Population::new();
+    for (&v, &count) in pop {
+        for w in rules.apply(v)? {
+            let total = next.entry(w).or_default();
+            *total = total
+                .checked_add(count)
+                .ok_or_else(|| anyhow!("more than {} stones with value {}", u128::MAX, w))?;
+        }
+    }
+    Ok(next)
+}
+
+/// Number of stones after `blinks` blinks, or an error saying which blink
+/// overflowed.
+pub fn count(stones: &[Stone], rules: &Rules, blinks: usize) -> Result<u128, Error> {
+    let mut pop = population(stones);
+    for i in 0..blinks {
+        pop = blink(&pop, rules).with_context(|| format!("blink {}", i + 1))?;
+    }
+    pop.values()
+        .try_fold(0u128, |sum, &n| sum.checked_add(n))
+        .ok_or_else(|| anyhow!("more than {} stones after {} blinks", u128::MAX, blinks))
+}
+
+fn mul(v: Stone, n: Stone) -> Result<Stone, Error> {
+    v.checked_mul(n)
+        .ok_or_else(|| anyhow!("{} * {} overflows", v, n))
+}
+
+fn split(v: Stone) -> Result<Vec<Stone>, Error> {
+    let m = (10 as Stone).pow(l10(v) / 2);
+    Ok(vec![v / m, v % m])
+}
+
+// Number of decimal digits.
+fn l10(v: Stone) -> u32 {
+    v.checked_ilog10().map_or(1, |d| d + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_l10() {
+        assert_eq!(l10(1), 1);
+        assert_eq!(l10(999), 3);
+        assert_eq!(l10(1000), 4);
+        assert_eq!(l10(17), 2);
+        assert_eq!(l10(0), 1);
+        assert_eq!(l10(u128::MAX), 39);
+    }
+
+    #[test]
+    fn sample() {
+        let rules = Rules::standard();
+        assert_eq!(count(&[125, 17], &rules, 6).unwrap(), 22);
+        assert_eq!(count(&[125, 17], &rules, 25).unwrap(), 55312);
+        assert_eq!(rules.apply(1000).unwrap(), vec![10, 0]);
+    }
+
+    #[test]
+    fn dsl() {
+        let standard = Rules::standard();
+        let parsed = Rules::parse("0 -> 1; even-digits -> split\n* -> *2024").unwrap();
+        for v in [0, 1, 10, 17, 125, 1000, 253000] {
+            assert_eq!(parsed.apply(v).unwrap(), standard.apply(v).unwrap());
+        }
+        assert_eq!(count(&[125, 17], &parsed, 25).unwrap(), 55312);
+
+        let rules = Rules::parse("%3 -> +1; 7 -> 0").unwrap();
+        assert_eq!(rules.apply(9).unwrap(), vec![10]);
+        assert_eq!(rules.apply(7).unwrap(), vec![0]);
+        assert_eq!(rules.apply(8).unwrap(), vec![8]);
+
+        assert!(Rules::parse("0 => 1").is_err());
+        assert!(Rules::parse("x -> 1").is_err());
+        assert!(Rules::parse("0 -> /2").is_err());
+        assert!(Rules::parse("%0 -> 1").is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        // 10^36 has an odd number of digits and is too big to multiply.
+        let rules = Rules::standard();
+        let err = count(&[1, 10u128.pow(36)], &rules, 2).unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            format!("blink 1: {} * 2024 overflows", 10u128.pow(36))
+        );
+
+        // The number of stones doubles every other blink.
+        let rules = Rules::parse("1 -> 11; 11 -> split").unwrap();
+        assert_eq!(count(&[1], &rules, 2 * 127).unwrap(), 1 << 127);
+        assert!(count(&[1], &rules, 2 * 128).is_err());
+
+        assert!(parse("1 -2").is_err());
+        assert!(parse("340282366920938463463374607431768211456").is_err());
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{anyhow, bail, Context, Error, Result};
 use regex::Regex;
 use std::iter::Peekable;
 
-pub type Number = i64;
+pub type Number = i128;
 
 #[derive(Debug)]
 pub struct Machine {
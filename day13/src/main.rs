@@ -1,18 +1,61 @@
-use anyhow::{bail, Error};
+use anyhow::{bail, Context, Error};
+
+use solver::Pricing;
 
 mod input;
 mod part1;
 mod part2;
+mod solver;
+
+const USAGE: &str = "usage: day13 <filename> [--cost-a <n>] [--cost-b <n>] [--offset <n>] \
+    [--limit <n>|none]";
 
 fn main() -> Result<(), Error> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        bail!("expected <filename>");
+    let mut filename: Option<String> = None;
+    // Any pricing option adds a run priced like part1 with the given changes.
+    let mut custom: Option<Pricing> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if filename.replace(arg).is_some() {
+                bail!("more than one filename\n{}", USAGE);
+            }
+            continue;
+        }
+        let value = args
+            .next()
+            .with_context(|| format!("{} needs a value\n{}", arg, USAGE))?;
+        let number = || {
+            value
+                .parse()
+                .with_context(|| format!("invalid {} {:?}", arg, value))
+        };
+        let pricing = custom.get_or_insert(Pricing::PART1);
+        match arg.as_str() {
+            "--cost-a" => pricing.cost_a = number()?,
+            "--cost-b" => pricing.cost_b = number()?,
+            "--offset" => pricing.offset = number()?,
+            "--limit" if value == "none" => pricing.press_limit = None,
+            "--limit" => pricing.press_limit = Some(number()?),
+            _ => bail!("unexpected argument {:?}\n{}", arg, USAGE),
+        }
     }
-    let filename = &args[1];
-    let filedata = std::fs::read_to_string(filename)?;
+    let Some(filename) = filename else {
+        bail!("expected <filename>\n{}", USAGE);
+    };
+    if let Some(pricing) = &custom {
+        if pricing.cost_a < 0 || pricing.cost_b < 0 {
+            bail!("button costs can't be negative");
+        }
+    }
+
+    let filedata = std::fs::read_to_string(&filename)?;
     let input = input::Parser::new().parse(&filedata)?;
     println!("part1={}", part1::eval(&input));
     println!("part2={}", part2::eval(&input));
+    if let Some(pricing) = &custom {
+        println!("custom={}", solver::eval(&input, pricing));
+    }
     Ok(())
 }
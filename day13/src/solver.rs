@@ -0,0 +1,348 @@
+use crate::input::{Input, Machine, Number};
+
+/// What pressing the buttons costs and where the prizes really are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pricing {
+    /// Tokens per press of A and of B. Neither can be negative.
+    pub cost_a: Number,
+    pub cost_b: Number,
+    /// Added to both coordinates of every prize.
+    pub offset: Number,
+    /// Most presses allowed per button, if there's a limit.
+    pub press_limit: Option<Number>,
+}
+
+impl Pricing {
+    pub const PART1: Pricing = Pricing {
+        cost_a: 3,
+        cost_b: 1,
+        offset: 0,
+        press_limit: Some(100),
+    };
+
+    pub const PART2: Pricing = Pricing {
+        offset: 10_000_000_000_000,
+        press_limit: None,
+        ..Pricing::PART1
+    };
+}
+
+/// Total tokens to win every prize that can be won.
+pub fn eval(input: &Input, pricing: &Pricing) -> Number {
+    input
+        .machines
+        .iter()
+        .filter_map(|m| eval_machine(m, pricing))
+        .sum()
+}
+
+/// Fewest tokens to win the machine's prize, or None if it can't be won.
+pub fn eval_machine(m: &Machine, pricing: &Pricing) -> Option<Number> {
+    let (a, b) = solve(m, pricing)?;
+    Some(a * pricing.cost_a + b * pricing.cost_b)
+}
+
+/// Finds how many times to press A and B to land exactly on the prize for
+/// the fewest tokens. Neither button can be pressed a negative number of
+/// times, or more than the press limit.
+///
+/// When the buttons point in different directions there's at most one way
+/// to reach the prize. When they're collinear (parallel, identical or zero)
+/// there can be many, and the cheapest is found with extended Euclid.
+pub fn solve(m: &Machine, pricing: &Pricing) -> Option<(Number, Number)> {
+    assert!(pricing.cost_a >= 0 && pricing.cost_b >= 0);
+    let a = (m.x_a, m.y_a);
+    let b = (m.x_b, m.y_b);
+    let p = (m.x_p + pricing.offset, m.y_p + pricing.offset);
+    let limit = pricing.press_limit;
+
+    let (na, nb) = match cross(a, b) {
+        0 => solve_collinear(a, b, p, pricing)?,
+        d => (idiv(cross(p, b), d)?, idiv(cross(a, p), d)?),
+    };
+    let allowed = |n: Number| n >= 0 && limit.is_none_or(|l| n <= l);
+    (allowed(na) && allowed(nb)).then_some((na, nb))
+}
+
+fn cross(u: (Number, Number), v: (Number, Number)) -> Number {
+    u.0 * v.1 - u.1 * v.0
+}
+
+fn idiv(n: Number, d: Number) -> Option<Number> {
+    if n % d == 0 {
+        Some(n / d)
+    } else {
+        None
+    }
+}
+
+// Both buttons lie on one line through the origin, so the prize must too,
+// and along that line the problem is a single equation
+// `na * alpha + nb * beta = pi`.
+fn solve_collinear(
+    a: (Number, Number),
+    b: (Number, Number),
+    p: (Number, Number),
+    pricing: &Pricing,
+) -> Option<(Number, Number)> {
+    let dir = if a != (0, 0) { a } else { b };
+    if dir == (0, 0) {
+        // Neither button moves the claw.
+        return (p == (0, 0)).then_some((0, 0));
+    }
+    if cross(dir, p) != 0 {
+        return None;
+    }
+
+    // Any coordinate the line isn't flat along works; the other one then
+    // follows.
+    let (alpha, beta, pi) = if dir.0 != 0 {
+        (a.0, b.0, p.0)
+    } else {
+        (a.1, b.1, p.1)
+    };
+    solve_line(alpha, beta, pi, pricing)
+}
+
+// Cheapest (na, nb) within the press limit with `na * alpha + nb * beta =
+// pi`, where alpha and beta aren't both zero. Solutions that only need one
+// button are left for the caller to check against the limit.
+fn solve_line(
+    alpha: Number,
+    beta: Number,
+    pi: Number,
+    pricing: &Pricing,
+) -> Option<(Number, Number)> {
+    if beta == 0 {
+        return Some((idiv(pi, alpha)?, 0));
+    }
+    if alpha == 0 {
+        return Some((0, idiv(pi, beta)?));
+    }
+
+    let (g, x, y) = ext_gcd(alpha, beta);
+    let scale = idiv(pi, g)?;
+    let (a0, b0) = (x * scale, y * scale);
+
+    // Every solution is (a0 + k * da, b0 + k * db) for some integer k.
+    let (da, db) = (beta / g, -alpha / g);
+    let (mut lo, mut hi) = (None, None);
+    let mut at_least = |k: Number| lo = Some(lo.map_or(k, |lo: Number| lo.max(k)));
+    let mut at_most = |k: Number| hi = Some(hi.map_or(k, |hi: Number| hi.min(k)));
+    for (n0, dn) in [(a0, da), (b0, db)] {
+        // 0 <= n0 + k * dn <= limit
+        if dn > 0 {
+            at_least(ceil_div(-n0, dn));
+            if let Some(limit) = pricing.press_limit {
+                at_most(floor_div(limit - n0, dn));
+            }
+        } else {
+            at_most(floor_div(n0, -dn));
+            if let Some(limit) = pricing.press_limit {
+                at_least(ceil_div(n0 - limit, -dn));
+            }
+        }
+    }
+    if let (Some(lo), Some(hi)) = (lo, hi) {
+        if lo > hi {
+            return None;
+        }
+    }
+
+    // The cost is linear in k, so the cheapest solution is at one end of
+    // the range. With non-negative costs the cost can't keep falling
+    // towards an open end, since both press counts grow that way.
+    let slope = pricing.cost_a * da + pricing.cost_b * db;
+    let k = if slope > 0 {
+        lo?
+    } else if slope < 0 {
+        hi?
+    } else {
+        lo.or(hi)?
+    };
+    Some((a0 + k * da, b0 + k * db))
+}
+
+// Returns (g, x, y) with g = gcd(a, b) > 0 and a * x + b * y = g.
+fn ext_gcd(a: Number, b: Number) -> (Number, Number, Number) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+fn floor_div(n: Number, d: Number) -> Number {
+    n.div_euclid(d)
+}
+
+fn ceil_div(n: Number, d: Number) -> Number {
+    -(-n).div_euclid(d)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn machine(a: (Number, Number), b: (Number, Number), p: (Number, Number)) -> Machine {
+        Machine {
+            x_a: a.0,
+            y_a: a.1,
+            x_b: b.0,
+            y_b: b.1,
+            x_p: p.0,
+            y_p: p.1,
+        }
+    }
+
+    fn costs(cost_a: Number, cost_b: Number) -> Pricing {
+        Pricing {
+            cost_a,
+            cost_b,
+            offset: 0,
+            press_limit: None,
+        }
+    }
+
+    fn cheapest(m: &Machine) -> Option<(Number, Number)> {
+        solve(m, &costs(3, 1))
+    }
+
+    #[test]
+    fn independent() {
+        let m = machine((94, 34), (22, 67), (8400, 5400));
+        assert_eq!(cheapest(&m), Some((80, 40)));
+        let m = machine((26, 66), (67, 21), (12748, 12176));
+        assert_eq!(cheapest(&m), None);
+        // Reachable only by pressing A -1 times.
+        let m = machine((1, 0), (0, 1), (-1, 2));
+        assert_eq!(cheapest(&m), None);
+    }
+
+    #[test]
+    fn parallel() {
+        // 2a + 3b = 12: (0, 4) costs 4, (3, 2) 11 and (6, 0) 18.
+        let m = machine((2, 4), (3, 6), (12, 24));
+        assert_eq!(cheapest(&m), Some((0, 4)));
+        // With B dear, A wins.
+        assert_eq!(solve(&m, &costs(1, 10)), Some((6, 0)));
+        // On the line but no non-negative combination gets there.
+        let m = machine((2, 2), (3, 3), (1, 1));
+        assert_eq!(cheapest(&m), None);
+        // Off the line.
+        let m = machine((1, 1), (2, 2), (3, 4));
+        assert_eq!(cheapest(&m), None);
+        // Opposite directions: a - b = 2 has no upper bound on presses.
+        let m = machine((1, 1), (-1, -1), (2, 2));
+        assert_eq!(cheapest(&m), Some((2, 0)));
+        let m = machine((1, 1), (-1, -1), (-2, -2));
+        assert_eq!(cheapest(&m), Some((0, 2)));
+        // Vertical line.
+        let m = machine((0, 2), (0, 5), (0, 9));
+        assert_eq!(cheapest(&m), Some((2, 1)));
+    }
+
+    #[test]
+    fn identical() {
+        let m = machine((1, 1), (1, 1), (5, 5));
+        assert_eq!(cheapest(&m), Some((0, 5)));
+        assert_eq!(solve(&m, &costs(1, 3)), Some((5, 0)));
+    }
+
+    #[test]
+    fn zero_vectors() {
+        let m = machine((0, 0), (2, 3), (4, 6));
+        assert_eq!(cheapest(&m), Some((0, 2)));
+        let m = machine((2, 3), (0, 0), (4, 6));
+        assert_eq!(cheapest(&m), Some((2, 0)));
+        let m = machine((0, 0), (0, 0), (0, 0));
+        assert_eq!(cheapest(&m), Some((0, 0)));
+        let m = machine((0, 0), (0, 0), (1, 0));
+        assert_eq!(cheapest(&m), None);
+    }
+
+    #[test]
+    fn press_limit() {
+        let limited = |limit| Pricing {
+            press_limit: Some(limit),
+            ..costs(3, 1)
+        };
+        let m = machine((94, 34), (22, 67), (8400, 5400));
+        assert_eq!(solve(&m, &limited(80)), Some((80, 40)));
+        assert_eq!(solve(&m, &limited(79)), None);
+        // 2a + 3b = 12: (0, 4) is out of reach, (3, 2) isn't.
+        let m = machine((2, 4), (3, 6), (12, 24));
+        assert_eq!(solve(&m, &limited(3)), Some((3, 2)));
+        assert_eq!(solve(&m, &limited(2)), None);
+        // a - b = 2 with both presses capped.
+        let m = machine((1, 1), (-1, -1), (2, 2));
+        assert_eq!(solve(&m, &costs(1, 3)), Some((2, 0)));
+        assert_eq!(solve(&m, &limited(1)), None);
+        let m = machine((0, 0), (2, 3), (200, 300));
+        assert_eq!(solve(&m, &limited(99)), None);
+    }
+
+    #[test]
+    fn large_offset() {
+        let pricing = Pricing {
+            offset: 1 << 100,
+            ..Pricing::PART2
+        };
+        // Far past i64, and B alone is cheapest.
+        let m = machine((1, 1), (2, 2), (0, 0));
+        assert_eq!(eval_machine(&m, &pricing), Some(1 << 99));
+        let m = machine((3, 1), (1, 3), (0, 0));
+        assert_eq!(eval_machine(&m, &pricing), Some((1 << 98) * 4));
+    }
+
+    #[test]
+    fn collinear_matches_brute_force() {
+        let mut seed: u32 = 7;
+        let mut next = |n: i32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) % (2 * n as u32 + 1)) as i32 - n
+        };
+        for _ in 0..2000 {
+            let dir = (next(3) as Number, next(3) as Number);
+            let (ka, kb) = (next(4) as Number, next(4) as Number);
+            let kp = next(30) as Number;
+            let m = machine(
+                (dir.0 * ka, dir.1 * ka),
+                (dir.0 * kb, dir.1 * kb),
+                (dir.0 * kp, dir.1 * kp),
+            );
+            let (cost_a, cost_b) = (next(3).abs() as Number, next(3).abs() as Number);
+            let limit = (next(1) == 0).then_some(next(20).abs() as Number);
+            let pricing = Pricing {
+                press_limit: limit,
+                ..costs(cost_a, cost_b)
+            };
+            let max = limit.unwrap_or(60);
+            let brute = (0..=max)
+                .flat_map(|a| (0..=max).map(move |b| (a, b)))
+                .filter(|&(a, b)| a * m.x_a + b * m.x_b == m.x_p && a * m.y_a + b * m.y_b == m.y_p)
+                .map(|(a, b)| a * cost_a + b * cost_b)
+                .min();
+            let found = solve(&m, &pricing);
+            if let Some((a, b)) = found {
+                assert_eq!(a * m.x_a + b * m.x_b, m.x_p, "{:?}", m);
+                assert_eq!(a * m.y_a + b * m.y_b, m.y_p, "{:?}", m);
+            }
+            assert_eq!(
+                found.map(|(a, b)| a * cost_a + b * cost_b),
+                brute,
+                "{:?}",
+                m
+            );
+        }
+    }
+}
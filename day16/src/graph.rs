@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use crate::maze::{Maze, V};
+use crate::search::{CostModel, Graph, State};
+
+const DIRS: [V; 4] = [V(1, 0), V(0, 1), V(-1, 0), V(0, -1)];
+
+/// Returns a copy of the maze with every dead end walled off, repeatedly,
+/// until only corridors that lead somewhere are left. Starts and goals are
+/// never filled.
+pub fn fill_dead_ends(maze: &Maze) -> Maze {
+  let mut filled = Maze {
+    starts: maze.starts.clone(),
+    goals: maze.goals.clone(),
+    width: maze.width,
+    height: maze.height,
+    rows: maze.rows.clone(),
+  };
+  let is_dead_end = |m: &Maze, pos: V| {
+    m.is_valid_pos(pos)
+      && !m.starts.contains(&pos)
+      && !m.goals.contains(&pos)
+      && DIRS.iter().filter(|&&d| m.is_valid_pos(pos + d)).count() <= 1
+  };
+
+  let mut stack: Vec<V> = (0..maze.height)
+    .flat_map(|y| (0..maze.width).map(move |x| V(x, y)))
+    .filter(|&pos| is_dead_end(&filled, pos))
+    .collect();
+  while let Some(pos) = stack.pop() {
+    if !is_dead_end(&filled, pos) {
+      continue;
+    }
+    filled.rows[pos.y() as usize][pos.x() as usize] = false;
+    // Filling this one may have turned the tile before it into a dead end.
+    stack.extend(DIRS.iter().map(|&d| pos + d));
+  }
+  filled
+}
+
+/// A corridor between two junctions.
+struct Corridor {
+  /// Direction it leaves the junction in.
+  dir: V,
+  /// Tile by tile states along it, ending at the next junction.
+  states: Vec<State>,
+  turns: i64,
+}
+
+/// The maze reduced to its junctions, plus the starts and goals, connected by
+/// corridors. Searching it only visits the junctions.
+pub struct JunctionGraph {
+  starts: Vec<V>,
+  goals: Vec<V>,
+  corridors: Vec<Corridor>,
+  // Corridors leaving each junction.
+  exits: HashMap<V, Vec<usize>>,
+}
+
+impl JunctionGraph {
+  pub fn new(maze: &Maze) -> JunctionGraph {
+    let maze = &fill_dead_ends(maze);
+    let exits_of = |pos: V| {
+      DIRS
+        .into_iter()
+        .filter(move |&d| maze.is_valid_pos(pos + d))
+    };
+    let is_junction = |pos: V| {
+      maze.starts.contains(&pos)
+        || maze.goals.contains(&pos)
+        || exits_of(pos).count() >= 3
+    };
+
+    let mut graph = JunctionGraph {
+      starts: maze.starts.clone(),
+      goals: maze.goals.clone(),
+      corridors: Vec::new(),
+      exits: HashMap::new(),
+    };
+    for y in 0..maze.height {
+      for x in 0..maze.width {
+        let from = V(x, y);
+        if !maze.is_valid_pos(from) || !is_junction(from) {
+          continue;
+        }
+        let mut exits = Vec::new();
+        for dir in exits_of(from) {
+          // Everything between junctions has exactly two ways out, the one we
+          // came in through and the one to follow.
+          let mut states = vec![(from + dir, dir)];
+          let mut turns = 0;
+          loop {
+            let (pos, dir) = *states.last().unwrap();
+            if is_junction(pos) {
+              break;
+            }
+            let back = V(-dir.x(), -dir.y());
+            let Some(next) = exits_of(pos).find(|&d| d != back) else {
+              break;
+            };
+            if next != dir {
+              turns += 1;
+            }
+            states.push((pos + next, next));
+          }
+          exits.push(graph.corridors.len());
+          graph.corridors.push(Corridor { dir, states, turns });
+        }
+        graph.exits.insert(from, exits);
+      }
+    }
+    graph
+  }
+
+  pub fn junctions(&self) -> usize {
+    self.exits.len()
+  }
+
+  pub fn corridors(&self) -> usize {
+    self.corridors.len()
+  }
+}
+
+impl Graph for JunctionGraph {
+  type Edge = usize;
+
+  fn starts(&self) -> &[V] {
+    &self.starts
+  }
+
+  fn is_goal(&self, pos: V) -> bool {
+    self.goals.contains(&pos)
+  }
+
+  fn for_each_move<F>(&self, (pos, dir): State, costs: &CostModel, mut f: F)
+  where
+    F: FnMut(State, i64, usize),
+  {
+    for &i in self.exits.get(&pos).into_iter().flatten() {
+      let c = &self.corridors[i];
+      let Some(turn) = costs.turn_cost(dir, c.dir) else {
+        continue;
+      };
+      let cost =
+        turn + c.turns * costs.turn + c.states.len() as i64 * costs.step;
+      f(*c.states.last().unwrap(), cost, i);
+    }
+  }
+
+  fn path(&self, edge: usize, _to: State) -> Vec<State> {
+    self.corridors[edge].states.clone()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::search::BestPaths;
+
+  const SAMPLE: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+  #[test]
+  fn dead_ends() {
+    let maze = Maze::parse(
+      "#######
+#S...E#
+#.#.###
+#.#...#
+#######",
+    )
+    .unwrap();
+    let filled = fill_dead_ends(&maze);
+    let open: Vec<&str> =
+      ["#######", "#S...E#", "#######", "#######", "#######"].to_vec();
+    for (y, row) in open.iter().enumerate() {
+      for (x, c) in row.chars().enumerate() {
+        assert_eq!(filled.rows[y][x], c != '#', "{},{}", x, y);
+      }
+    }
+  }
+
+  #[test]
+  fn same_as_tiles() {
+    let maze = Maze::parse(SAMPLE).unwrap();
+    let graph = JunctionGraph::new(&maze);
+    let cost_models = [
+      CostModel::default(),
+      CostModel {
+        start_dir: None,
+        end_dir: Some(V(-1, 0)),
+        ..Default::default()
+      },
+      CostModel {
+        turn: 1,
+        allow_u_turn: false,
+        ..Default::default()
+      },
+    ];
+    for costs in cost_models {
+      let by_tile = BestPaths::search(&maze, &costs).unwrap();
+      let by_junction = BestPaths::search(&graph, &costs).unwrap();
+      assert_eq!(by_junction.score, by_tile.score);
+      assert_eq!(by_junction.tiles, by_tile.tiles);
+      assert!(by_junction.explored < by_tile.explored);
+
+      // The route has the same cost even if it's a different one.
+      let turns = by_junction
+        .route
+        .iter()
+        .filter(|&&m| m != crate::search::Move::Forward);
+      let steps = by_junction.route.len() - turns.clone().count();
+      assert_eq!(
+        steps as i64 * costs.step + turns.count() as i64 * costs.turn,
+        by_tile.score
+      );
+    }
+  }
+}
@@ -1,13 +1,90 @@
+mod graph;
+mod maze;
 mod part1;
 mod part2;
+mod search;
+
+use std::time::Instant;
+
+use graph::JunctionGraph;
+use maze::Maze;
+use search::{BestPaths, CostModel};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    let has_flag = |flag: &str| args.iter().any(|a| a == flag);
+    let (show, bench, multi) = (has_flag("--show"), has_flag("--bench"), has_flag("--multi"));
+    let args: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
     if args.len() != 2 {
-        panic!("Expected <filename>");
+        panic!("Expected <filename> [--show] [--bench] [--multi]");
     }
     let filename = &args[1];
     let filedata = std::fs::read_to_string(filename).unwrap();
-    println!("part1={}", part1::eval(&filedata));
-    println!("part2={}", part2::eval(&filedata));
+    let maze = if multi {
+        Maze::parse_multi(&filedata)
+    } else {
+        Maze::parse(&filedata)
+    };
+    let maze = match maze {
+        Ok(maze) => maze,
+        Err(err) => {
+            eprintln!("{}:{}", filename, err);
+            std::process::exit(1);
+        }
+    };
+    let (Some(score), Some(tiles)) = (part1::eval(&maze), part2::eval(&maze)) else {
+        eprintln!("{}: no route from any start to any end", filename);
+        std::process::exit(1);
+    };
+    println!("part1={}", score);
+    println!("part2={}", tiles);
+
+    if show {
+        let best = BestPaths::search(&maze, &CostModel::default()).unwrap();
+        println!();
+        if multi {
+            println!(
+                "best route: {},{} to {},{}",
+                best.start.x(),
+                best.start.y(),
+                best.goal.x(),
+                best.goal.y()
+            );
+        }
+        print!("{}", best.render_route(&maze));
+        println!();
+        print!("{}", best.render_tiles(&maze));
+    }
+    if bench {
+        bench_search(&maze);
+    }
+}
+
+// Compares searching the maze tile by tile with searching its junction graph.
+fn bench_search(maze: &Maze) {
+    let costs = CostModel::default();
+
+    let start = Instant::now();
+    let by_tile = BestPaths::search(maze, &costs).unwrap();
+    println!();
+    println!(
+        "tiles:     explored {} states in {:.3?}",
+        by_tile.explored,
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let graph = JunctionGraph::new(maze);
+    let built = start.elapsed();
+    let by_junction = BestPaths::search(&graph, &costs).unwrap();
+    println!(
+        "junctions: explored {} states in {:.3?} ({:.3?} to build {} junctions, {} corridors)",
+        by_junction.explored,
+        start.elapsed(),
+        built,
+        graph.junctions(),
+        graph.corridors()
+    );
+    assert_eq!(by_tile.score, by_junction.score);
+    assert_eq!(by_tile.tiles, by_junction.tiles);
 }
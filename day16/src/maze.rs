@@ -0,0 +1,267 @@
+use std::{
+    fmt,
+    ops::{Add, Sub},
+};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct V(pub i32, pub i32);
+
+impl V {
+    pub fn x(&self) -> i32 {
+        self.0
+    }
+    pub fn y(&self) -> i32 {
+        self.1
+    }
+    pub fn rotate90(&self) -> V {
+        V(-self.y(), self.x())
+    }
+}
+
+impl Add for V {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        V(self.x() + rhs.x(), self.y() + rhs.y())
+    }
+}
+
+impl Sub for V {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        V(self.x() - rhs.x(), self.y() - rhs.y())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Line and column (both 1-based) of a character that isn't `#`, `.`, `S`
+    /// or `E`.
+    InvalidChar {
+        line: usize,
+        col: usize,
+        c: char,
+    },
+    /// A row whose length differs from the first row's.
+    RowLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A second `S` when only one is allowed.
+    ExtraStart {
+        line: usize,
+        col: usize,
+    },
+    /// A second `E` when only one is allowed.
+    ExtraGoal {
+        line: usize,
+        col: usize,
+    },
+    NoStart,
+    NoGoal,
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar { line, col, c } => {
+                write!(f, "{}:{}: invalid maze character {:?}", line, col, c)
+            }
+            ParseError::RowLength {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: row has {} tiles, expected {}",
+                line, found, expected
+            ),
+            ParseError::ExtraStart { line, col } => {
+                write!(f, "{}:{}: more than one start", line, col)
+            }
+            ParseError::ExtraGoal { line, col } => {
+                write!(f, "{}:{}: more than one end", line, col)
+            }
+            ParseError::NoStart => write!(f, "no start"),
+            ParseError::NoGoal => write!(f, "no end"),
+            ParseError::Empty => write!(f, "empty maze"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub struct Maze {
+    /// Every `S`. There is exactly one unless parsed with `parse_multi`.
+    pub starts: Vec<V>,
+    /// Every `E`. There is exactly one unless parsed with `parse_multi`.
+    pub goals: Vec<V>,
+    pub width: i32,
+    pub height: i32,
+    pub rows: Vec<Vec<bool>>,
+}
+
+impl Maze {
+    pub fn parse(s: &str) -> Result<Maze, ParseError> {
+        Maze::parse_with(s, false)
+    }
+
+    /// Same as `parse`, but allows several starts and ends. Searching the
+    /// maze then finds the best route from any start to any end.
+    pub fn parse_multi(s: &str) -> Result<Maze, ParseError> {
+        Maze::parse_with(s, true)
+    }
+
+    fn parse_with(s: &str, multi: bool) -> Result<Maze, ParseError> {
+        let mut goals: Vec<V> = Vec::new();
+        let mut starts: Vec<V> = Vec::new();
+        let mut rows: Vec<Vec<bool>> = Vec::new();
+
+        for (n, raw) in s.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() {
+                continue;
+            }
+            // Columns count from the start of the raw line, indentation
+            // included.
+            let indent = raw.chars().count() - raw.trim_start().chars().count();
+            let j = rows.len() as i32;
+            let mut row: Vec<bool> = if j == 0 {
+                Vec::new()
+            } else {
+                Vec::with_capacity(rows[0].len())
+            };
+            for (i, c) in line.chars().enumerate() {
+                let (line, col) = (n + 1, indent + i + 1);
+                match c {
+                    '#' => {
+                        row.push(false);
+                    }
+                    '.' => {
+                        row.push(true);
+                    }
+                    'S' => {
+                        if !multi && !starts.is_empty() {
+                            return Err(ParseError::ExtraStart { line, col });
+                        }
+                        starts.push(V(i as i32, j));
+                        row.push(true);
+                    }
+                    'E' => {
+                        if !multi && !goals.is_empty() {
+                            return Err(ParseError::ExtraGoal { line, col });
+                        }
+                        goals.push(V(i as i32, j));
+                        row.push(true);
+                    }
+                    _ => return Err(ParseError::InvalidChar { line, col, c }),
+                }
+            }
+            if let Some(first) = rows.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError::RowLength {
+                        line: n + 1,
+                        expected: first.len(),
+                        found: row.len(),
+                    });
+                }
+            }
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if starts.is_empty() {
+            return Err(ParseError::NoStart);
+        }
+        if goals.is_empty() {
+            return Err(ParseError::NoGoal);
+        }
+
+        Ok(Maze {
+            starts,
+            goals,
+            width: rows[0].len().try_into().unwrap(),
+            height: rows.len().try_into().unwrap(),
+            rows,
+        })
+    }
+
+    pub fn is_valid_pos(&self, pos: V) -> bool {
+        pos.x() >= 0
+            && pos.x() < self.width
+            && pos.y() >= 0
+            && pos.y() < self.height
+            && self.rows[pos.y() as usize][pos.x() as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        let parse = |s: &str| Maze::parse(s).err();
+        assert_eq!(parse("#S.E#"), None);
+        assert_eq!(
+            parse("#S.E#\n#.x.#"),
+            Some(ParseError::InvalidChar {
+                line: 2,
+                col: 3,
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            parse("#S.E#\n#..#"),
+            Some(ParseError::RowLength {
+                line: 2,
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(
+            parse("#S.E#\n\n#S..#"),
+            Some(ParseError::ExtraStart { line: 3, col: 2 })
+        );
+        assert_eq!(
+            parse("#SE.E#"),
+            Some(ParseError::ExtraGoal { line: 1, col: 5 })
+        );
+        assert_eq!(
+            parse("  #x"),
+            Some(ParseError::InvalidChar {
+                line: 1,
+                col: 4,
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            parse("\t#S.E#\n\t#S..#"),
+            Some(ParseError::ExtraStart { line: 2, col: 3 })
+        );
+        assert_eq!(parse("#...E#"), Some(ParseError::NoStart));
+        assert_eq!(parse("#S..#"), Some(ParseError::NoGoal));
+        assert_eq!(parse("\n\n"), Some(ParseError::Empty));
+        assert_eq!(
+            ParseError::InvalidChar {
+                line: 2,
+                col: 3,
+                c: 'x'
+            }
+            .to_string(),
+            "2:3: invalid maze character 'x'"
+        );
+    }
+
+    #[test]
+    fn parse_multi() {
+        let maze = Maze::parse_multi("#S.E#\r\n#E.S#\r\n").unwrap();
+        assert_eq!(maze.starts, vec![V(1, 0), V(3, 1)]);
+        assert_eq!(maze.goals, vec![V(3, 0), V(1, 1)]);
+    }
+}
@@ -0,0 +1,587 @@
+use std::{
+  cmp::Ordering,
+  collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::maze::{Maze, V};
+
+const DIRS: [V; 4] = [V(1, 0), V(0, 1), V(-1, 0), V(0, -1)];
+
+/// Scoring rules for moving through the maze. The default is the puzzle's:
+/// 1 point per step, 1000 per quarter turn, starting out facing east and
+/// ending in any direction.
+#[derive(Debug, Clone, Copy)]
+pub struct CostModel {
+  pub step: i64,
+  /// Cost of each quarter turn. A U-turn is two of them.
+  pub turn: i64,
+  pub allow_u_turn: bool,
+  /// Direction faced at the start, or None to start facing any direction for
+  /// free.
+  pub start_dir: Option<V>,
+  /// Direction that must be faced at the goal, turning on the spot if needed,
+  /// or None for any direction.
+  pub end_dir: Option<V>,
+}
+
+impl Default for CostModel {
+  fn default() -> Self {
+    CostModel {
+      step: 1,
+      turn: 1000,
+      allow_u_turn: true,
+      start_dir: Some(V(1, 0)),
+      end_dir: None,
+    }
+  }
+}
+
+impl CostModel {
+  /// Cost of turning from `from` to face `to`, or None if that takes a U-turn
+  /// and they are not allowed.
+  pub fn turn_cost(&self, from: V, to: V) -> Option<i64> {
+    if from == to {
+      Some(0)
+    } else if from.rotate90() == to || to.rotate90() == from {
+      Some(self.turn)
+    } else if self.allow_u_turn {
+      Some(2 * self.turn)
+    } else {
+      None
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+  Forward,
+  /// Quarter turn counterclockwise on the spot.
+  Left,
+  /// Quarter turn clockwise on the spot.
+  Right,
+}
+
+/// Every optimal route through the maze, found with a single search.
+pub struct BestPaths {
+  pub score: i64,
+  /// Tiles that are part of at least one optimal route.
+  pub tiles: HashSet<V>,
+  /// Where `route` starts and ends. With several starts and ends, this is one
+  /// of the pairs with the best score.
+  pub start: V,
+  pub goal: V,
+  /// Direction faced at the start of `route`.
+  pub start_dir: V,
+  /// The moves of one of the optimal routes.
+  pub route: Vec<Move>,
+  /// Number of states taken off the queue during the search.
+  pub explored: usize,
+}
+
+/// Position and facing direction.
+pub type State = (V, V);
+
+/// Something to search for the best routes through: the maze itself, one
+/// tile at a time, or a simplified version of it.
+pub trait Graph {
+  /// Identifies a move for `path`.
+  type Edge: Copy;
+
+  fn starts(&self) -> &[V];
+  fn is_goal(&self, pos: V) -> bool;
+
+  /// Calls `f` with every state reachable in one move from `state`, along
+  /// with the cost of getting there and the move taken.
+  fn for_each_move<F>(&self, state: State, costs: &CostModel, f: F)
+  where
+    F: FnMut(State, i64, Self::Edge);
+
+  /// Tile by tile states visited by a move that ends in `to`, including `to`
+  /// itself.
+  fn path(&self, edge: Self::Edge, to: State) -> Vec<State>;
+}
+
+impl Graph for Maze {
+  type Edge = ();
+
+  fn starts(&self) -> &[V] {
+    &self.starts
+  }
+
+  fn is_goal(&self, pos: V) -> bool {
+    self.goals.contains(&pos)
+  }
+
+  fn for_each_move<F>(&self, (pos, dir): State, costs: &CostModel, mut f: F)
+  where
+    F: FnMut(State, i64, ()),
+  {
+    for new_dir in DIRS {
+      let new_pos = pos + new_dir;
+      if !self.is_valid_pos(new_pos) {
+        continue;
+      }
+      let Some(turn) = costs.turn_cost(dir, new_dir) else {
+        continue;
+      };
+      f((new_pos, new_dir), turn + costs.step, ());
+    }
+  }
+
+  fn path(&self, _edge: (), to: State) -> Vec<State> {
+    vec![to]
+  }
+}
+
+struct OrdState(i64, State);
+
+impl PartialEq for OrdState {
+  fn eq(&self, other: &Self) -> bool {
+    self.0 == other.0
+  }
+}
+
+impl Eq for OrdState {}
+
+impl Ord for OrdState {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.0.cmp(&self.0)
+  }
+}
+
+impl PartialOrd for OrdState {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+// How a state was reached: the previous state and the move from it, or None
+// for turning on the spot at the goal.
+type Link<E> = (State, Option<E>);
+
+struct Search<'a, G: Graph> {
+  graph: &'a G,
+  costs: &'a CostModel,
+  pqueue: BinaryHeap<OrdState>,
+  // Lowest known score for each state.
+  dist: HashMap<State, i64>,
+  // The ways each state can be reached with its lowest score.
+  preds: HashMap<State, Vec<Link<G::Edge>>>,
+  explored: usize,
+}
+
+impl BestPaths {
+  pub fn search<G: Graph>(graph: &G, costs: &CostModel) -> Option<BestPaths> {
+    // With free steps there could be loops of optimal moves, and no single
+    // route to report.
+    assert!(costs.step > 0 && costs.turn >= 0);
+    let mut srch = Search {
+      graph,
+      costs,
+      pqueue: BinaryHeap::new(),
+      dist: HashMap::new(),
+      preds: HashMap::new(),
+      explored: 0,
+    };
+
+    let (score, goals) = srch.run()?;
+    let states = srch.one_route(goals[0]);
+    let tiles = srch.backtrack(goals);
+    Some(BestPaths {
+      score,
+      tiles,
+      start: states[0].0,
+      goal: states.last().unwrap().0,
+      start_dir: states[0].1,
+      route: moves(&states),
+      explored: srch.explored,
+    })
+  }
+
+  /// Draws the maze with the route's direction of travel on each tile it
+  /// leaves, like the puzzle examples.
+  pub fn render_route(&self, maze: &Maze) -> String {
+    let mut grid = maze_grid(maze);
+    let mut pos = self.start;
+    let mut dir = self.start_dir;
+    for m in &self.route {
+      match m {
+        Move::Forward => {
+          grid[pos.y() as usize][pos.x() as usize] = arrow(dir);
+          pos = pos + dir;
+        }
+        Move::Left => dir = dir.rotate90().rotate90().rotate90(),
+        Move::Right => dir = dir.rotate90(),
+      }
+    }
+    grid_string(maze, grid)
+  }
+
+  /// Draws the maze with every tile on an optimal route marked `O`.
+  pub fn render_tiles(&self, maze: &Maze) -> String {
+    let mut grid = maze_grid(maze);
+    for pos in &self.tiles {
+      grid[pos.y() as usize][pos.x() as usize] = 'O';
+    }
+    grid_string(maze, grid)
+  }
+}
+
+fn maze_grid(maze: &Maze) -> Vec<Vec<char>> {
+  maze
+    .rows
+    .iter()
+    .map(|r| r.iter().map(|&c| if c { '.' } else { '#' }).collect())
+    .collect()
+}
+
+fn grid_string(maze: &Maze, mut grid: Vec<Vec<char>>) -> String {
+  for pos in &maze.starts {
+    grid[pos.y() as usize][pos.x() as usize] = 'S';
+  }
+  for pos in &maze.goals {
+    grid[pos.y() as usize][pos.x() as usize] = 'E';
+  }
+  let mut s = String::new();
+  for r in grid {
+    s.extend(r);
+    s.push('\n');
+  }
+  s
+}
+
+fn arrow(dir: V) -> char {
+  match dir {
+    V(1, 0) => '>',
+    V(0, 1) => 'v',
+    V(-1, 0) => '<',
+    V(0, -1) => '^',
+    _ => panic!("not a direction"),
+  }
+}
+
+// The moves that take you through consecutive states.
+fn moves(states: &[State]) -> Vec<Move> {
+  let mut moves = Vec::new();
+  for w in states.windows(2) {
+    let ((from_pos, from_dir), (to_pos, to_dir)) = (w[0], w[1]);
+    if from_dir.rotate90() == to_dir {
+      moves.push(Move::Right);
+    } else if to_dir.rotate90() == from_dir {
+      moves.push(Move::Left);
+    } else if from_dir != to_dir {
+      moves.extend([Move::Right, Move::Right]);
+    }
+    if from_pos != to_pos {
+      moves.push(Move::Forward);
+    }
+  }
+  moves
+}
+
+impl<G: Graph> Search<'_, G> {
+  // Dijkstra over (pos, dir) states, starting from every start at once.
+  // Returns the best score and every goal state reached with it.
+  fn run(&mut self) -> Option<(i64, Vec<State>)> {
+    let start_dirs = match self.costs.start_dir {
+      Some(dir) => vec![dir],
+      None => DIRS.to_vec(),
+    };
+    for &pos in self.graph.starts() {
+      for &dir in &start_dirs {
+        let start = (pos, dir);
+        self.dist.insert(start, 0);
+        self.pqueue.push(OrdState(0, start));
+      }
+    }
+
+    let mut best: Option<i64> = None;
+    let mut goals = Vec::new();
+
+    while let Some(OrdState(score, state)) = self.pqueue.pop() {
+      if score > self.dist[&state] {
+        // Stale entry; the state was reached more cheaply since.
+        continue;
+      }
+      if let Some(best) = best {
+        if score > best {
+          break;
+        }
+      }
+      self.explored += 1;
+
+      let (pos, dir) = state;
+      if self.graph.is_goal(pos) {
+        match self.costs.end_dir {
+          Some(end_dir) if dir != end_dir => {
+            // Turning on the spot to face the required direction.
+            if let Some(cost) = self.costs.turn_cost(dir, end_dir) {
+              self.relax((state, None), (pos, end_dir), score + cost);
+            }
+          }
+          _ => {
+            best = Some(score);
+            goals.push(state);
+            continue;
+          }
+        }
+      }
+
+      let (graph, costs) = (self.graph, self.costs);
+      graph.for_each_move(state, costs, |to, cost, edge| {
+        self.relax((state, Some(edge)), to, score + cost)
+      });
+    }
+
+    best.map(|best| (best, goals))
+  }
+
+  fn relax(&mut self, from: Link<G::Edge>, to: State, score: i64) {
+    match self.dist.get(&to) {
+      Some(&prev) if score > prev => {}
+      Some(&prev) if score == prev => {
+        self.preds.get_mut(&to).unwrap().push(from);
+      }
+      _ => {
+        self.dist.insert(to, score);
+        self.preds.insert(to, vec![from]);
+        self.pqueue.push(OrdState(score, to));
+      }
+    }
+  }
+
+  fn link_path(&self, (_, edge): Link<G::Edge>, to: State) -> Vec<State> {
+    match edge {
+      Some(edge) => self.graph.path(edge, to),
+      None => vec![to],
+    }
+  }
+
+  // Follows the first predecessor of each state back from `goal` to a start
+  // state, and returns the tile by tile states of the route in order.
+  fn one_route(&self, goal: State) -> Vec<State> {
+    let mut links = Vec::new();
+    let mut state = goal;
+    while let Some(preds) = self.preds.get(&state) {
+      links.push((preds[0], state));
+      state = preds[0].0;
+    }
+    let mut states = vec![state];
+    for &(link, to) in links.iter().rev() {
+      states.extend(self.link_path(link, to));
+    }
+    states
+  }
+
+  // Walks the predecessor graph back from the goal states and collects the
+  // tiles of every state and move on the way.
+  fn backtrack(&self, goals: Vec<State>) -> HashSet<V> {
+    let mut tiles = HashSet::new();
+    let mut visited: HashSet<State> = goals.iter().copied().collect();
+    let mut stack = goals;
+    while let Some(state) = stack.pop() {
+      tiles.insert(state.0);
+      for &link in self.preds.get(&state).into_iter().flatten() {
+        tiles
+          .extend(self.link_path(link, state).into_iter().map(|(pos, _)| pos));
+        if visited.insert(link.0) {
+          stack.push(link.0);
+        }
+      }
+    }
+    tiles
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let maze = Maze::parse(
+      "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############",
+    )
+    .unwrap();
+    let best = BestPaths::search(&maze, &CostModel::default()).unwrap();
+    assert_eq!(best.score, 7036);
+    assert_eq!(best.tiles.len(), 45);
+
+    let steps = best.route.iter().filter(|&&m| m == Move::Forward).count();
+    assert_eq!(
+      steps as i64 + 1000 * (best.route.len() - steps) as i64,
+      7036
+    );
+    // The route only goes over best tiles.
+    let render = best.render_route(&maze);
+    for (y, row) in render.lines().enumerate() {
+      for (x, c) in row.chars().enumerate() {
+        if "<>^v".contains(c) {
+          assert!(best.tiles.contains(&V(x as i32, y as i32)));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn route() {
+    let maze = Maze::parse(
+      "######
+#...E#
+#.####
+#S...#
+######",
+    )
+    .unwrap();
+    let best = BestPaths::search(&maze, &CostModel::default()).unwrap();
+    assert_eq!(best.score, 2005);
+    use Move::*;
+    assert_eq!(
+      best.route,
+      vec![Left, Forward, Forward, Right, Forward, Forward, Forward]
+    );
+    assert_eq!(
+      best.render_route(&maze),
+      "######
+#>>>E#
+#^####
+#S...#
+######
+"
+    );
+    assert_eq!(
+      best.render_tiles(&maze),
+      "######
+#OOOE#
+#O####
+#S...#
+######
+"
+    );
+  }
+
+  #[test]
+  fn sample_large() {
+    let maze = Maze::parse(
+      "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################",
+    )
+    .unwrap();
+    let best = BestPaths::search(&maze, &CostModel::default()).unwrap();
+    assert_eq!(best.score, 11048);
+    assert_eq!(best.tiles.len(), 64);
+  }
+
+  #[test]
+  fn cost_model() {
+    let maze = Maze::parse(
+      "#####
+#E.S#
+#####",
+    )
+    .unwrap();
+    let search =
+      |costs: CostModel| BestPaths::search(&maze, &costs).map(|b| b.score);
+
+    // Starting east against a wall means turning around first.
+    assert_eq!(search(CostModel::default()), Some(2002));
+    let no_u_turn = CostModel {
+      allow_u_turn: false,
+      ..Default::default()
+    };
+    assert_eq!(search(no_u_turn), None);
+
+    let any_start = CostModel {
+      start_dir: None,
+      ..Default::default()
+    };
+    assert_eq!(search(any_start), Some(2));
+    let face_north = CostModel {
+      end_dir: Some(V(0, -1)),
+      ..any_start
+    };
+    assert_eq!(search(face_north), Some(1002));
+    let face_east = CostModel {
+      end_dir: Some(V(1, 0)),
+      ..any_start
+    };
+    assert_eq!(search(face_east), Some(2002));
+    let face_east_no_u_turn = CostModel {
+      allow_u_turn: false,
+      ..face_east
+    };
+    assert_eq!(search(face_east_no_u_turn), None);
+
+    let cheap_turns = CostModel {
+      step: 2,
+      turn: 1,
+      ..Default::default()
+    };
+    assert_eq!(search(cheap_turns), Some(6));
+  }
+
+  #[test]
+  fn multiple_starts_and_goals() {
+    let maze = Maze::parse_multi(
+      "##########
+#S......E#
+#.########
+#...S..E.#
+##########",
+    )
+    .unwrap();
+    let best = BestPaths::search(&maze, &CostModel::default()).unwrap();
+    assert_eq!(best.score, 3);
+    assert_eq!((best.start, best.goal), (V(4, 3), V(7, 3)));
+    assert_eq!(best.tiles.len(), 4);
+
+    let graph = crate::graph::JunctionGraph::new(&maze);
+    let by_junction = BestPaths::search(&graph, &CostModel::default()).unwrap();
+    assert_eq!(
+      (by_junction.start, by_junction.goal),
+      (best.start, best.goal)
+    );
+    assert_eq!(by_junction.tiles, best.tiles);
+  }
+
+  #[test]
+  fn unreachable() {
+    let maze = Maze::parse("#####\n#S#E#\n#####").unwrap();
+    assert!(BestPaths::search(&maze, &CostModel::default()).is_none());
+    assert_eq!(crate::part1::eval(&maze), None);
+    assert_eq!(crate::part2::eval(&maze), None);
+
+    // Only the second start connects, turning around to head west.
+    let maze = Maze::parse_multi("#######\n#S#E.S#\n#######").unwrap();
+    assert_eq!(crate::part1::eval(&maze), Some(2002));
+  }
+}
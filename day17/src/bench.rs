@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+use crate::vm::{self, Registers};
+
+// Candidates start here so that each run of the puzzle program produces a
+// full 16-value output, as it does while searching for part2.
+const BASE: u64 = 1 << 45;
+
+// Evaluates `n` consecutive A candidates with both the interpreter and the
+// compiled program and reports throughput for each.
+pub fn run(prog: &[i32], n: u64) {
+  let start = Instant::now();
+  let mut sum = 0usize;
+  for a in BASE..BASE + n {
+    sum += vm::eval(prog, Registers::with_a(a)).len();
+  }
+  report("interpreted", n, start.elapsed(), sum);
+
+  let start = Instant::now();
+  let compiled = vm::compile::<u64>(prog);
+  let mut out = Vec::new();
+  let mut sum = 0usize;
+  for a in BASE..BASE + n {
+    compiled.eval_into(Registers::with_a(a), &mut out);
+    sum += out.len();
+  }
+  report("compiled", n, start.elapsed(), sum);
+}
+
+fn report(name: &str, n: u64, elapsed: Duration, outputs: usize) {
+  println!(
+    "{}: {} evals in {:.3?} ({:.2} Mevals/s, {} outputs)",
+    name,
+    n,
+    elapsed,
+    n as f64 / elapsed.as_secs_f64() / 1e6,
+    outputs
+  );
+}
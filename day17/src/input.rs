@@ -1,8 +1,10 @@
+use crate::vm::{Register, Registers};
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Input {
-  pub a: i32,
-  pub b: i32,
-  pub c: i32,
+  pub a: u128,
+  pub b: u128,
+  pub c: u128,
   pub prog: Vec<i32>,
 }
 
@@ -24,9 +26,18 @@ impl Input {
         .collect(),
     }
   }
+
+  /// The initial registers, or None if a value does not fit in `R`.
+  pub fn registers<R: Register>(&self) -> Option<Registers<R>> {
+    Some(Registers {
+      a: self.a.try_into().ok()?,
+      b: self.b.try_into().ok()?,
+      c: self.c.try_into().ok()?,
+    })
+  }
 }
 
-fn parse_kv<'a, 'b>(s: &'a str, expected: &'b str) -> &'a str {
+fn parse_kv<'a>(s: &'a str, expected: &str) -> &'a str {
   let (key, value) = s.split_once(":").unwrap();
   assert_eq!(key, expected);
   value.trim()
@@ -54,4 +65,25 @@ Program: 0,1,5,4,3,0";
       }
     )
   }
+
+  #[test]
+  fn parse_wide() {
+    let s = "Register A: 340282366920938463463374607431768211455
+Register B: 18446744073709551616
+Register C: 7
+
+Program: 0,3,5,4,3,0";
+    let input = Input::parse(s);
+    assert_eq!(input.a, u128::MAX);
+    assert_eq!(input.b, 1 << 64);
+    assert_eq!(input.registers::<u64>(), None);
+    assert_eq!(
+      input.registers::<u128>(),
+      Some(Registers {
+        a: u128::MAX,
+        b: 1 << 64,
+        c: 7
+      })
+    );
+  }
 }
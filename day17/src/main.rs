@@ -1,3 +1,4 @@
+mod bench;
 mod input;
 mod part1;
 mod part2;
@@ -5,7 +6,12 @@ mod vm;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    assert_eq!(args.len(), 2);
+    if args.len() == 3 && args[1] == "--bench" {
+        let n: u64 = args[2].parse().unwrap();
+        bench::run(part2::PROG, n);
+        return;
+    }
+    assert_eq!(args.len(), 2, "Expected <filename> or --bench <count>");
     let filename = &args[1];
     let filedata = std::fs::read_to_string(filename).unwrap();
     println!("part1={}", part1::eval(&filedata));
@@ -3,6 +3,13 @@ use crate::vm;
 
 pub fn eval(s: &str) -> String {
     let input = Input::parse(s);
-    let res = vm::eval(&input.prog, input.a as i64);
-    res.iter().map(|v|v.to_string()).collect::<Vec<String>>().join(",")
+    // Use 64-bit registers unless the input needs more.
+    let res = match input.registers::<u64>() {
+        Some(regs) => vm::eval(&input.prog, regs),
+        None => vm::eval(&input.prog, input.registers::<u128>().unwrap()),
+    };
+    res.iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
 }
@@ -1,6 +1,6 @@
-use crate::vm;
+use crate::vm::{self, Compiled, Registers};
 
-const PROG: &[i32] = &[2, 4, 1, 5, 7, 5, 0, 3, 4, 0, 1, 6, 5, 5, 3, 0];
+pub const PROG: &[i32] = &[2, 4, 1, 5, 7, 5, 0, 3, 4, 0, 1, 6, 5, 5, 3, 0];
 // Notes:
 //
 //  2 4  bst  B = A % 8    // Use first 3 bits of A.
@@ -15,13 +15,20 @@ const PROG: &[i32] = &[2, 4, 1, 5, 7, 5, 0, 3, 4, 0, 1, 6, 5, 5, 3, 0];
 // Each output is the first 3 bits (B) xor'ed with 3 bits (C) that are 0-7 bits
 // from the beginning of the input.
 
-pub fn eval() -> i64 {
+pub fn eval() -> u64 {
   let mut r: Vec<u8> = PROG.iter().map(|&e| e as u8).collect();
   r.reverse();
-  encode(&r, 0, &[]).unwrap()
+  let prog = vm::compile(PROG);
+  encode(&prog, &mut Vec::new(), &r, 0, &[]).unwrap()
 }
 
-fn encode(vs: &[u8], a: i64, exp: &[u8]) -> Option<i64> {
+fn encode(
+  prog: &Compiled<u64>,
+  out: &mut Vec<u8>,
+  vs: &[u8],
+  a: u64,
+  exp: &[u8],
+) -> Option<u64> {
   if vs.is_empty() {
     return Some(a);
   }
@@ -38,12 +45,12 @@ fn encode(vs: &[u8], a: i64, exp: &[u8]) -> Option<i64> {
     let b2 = b ^ 5;
     let b3 = b2 ^ 6;
     let c = b3 ^ v;
-    let x = (a << 3) | (b as i64);
-    let x = x | ((c as i64) << b2);
+    let x = (a << 3) | (b as u64);
+    let x = x | ((c as u64) << b2);
 
-    let res = vm::eval(PROG, x);
-    if !res.is_empty() && res == new_exp {
-      if let Some(a) = encode(vs, x, &new_exp) {
+    prog.eval_into(Registers::with_a(x), out);
+    if !out.is_empty() && *out == new_exp {
+      if let Some(a) = encode(prog, out, vs, x, &new_exp) {
         if lowest.is_none() || a < lowest.unwrap() {
           lowest = Some(a);
         }
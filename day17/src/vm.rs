@@ -1,18 +1,85 @@
-struct VM {
-  a: i64,
-  b: i64,
-  c: i64,
+use std::fmt::Debug;
+use std::ops::{BitXor, BitXorAssign};
+
+/// Integer type the VM uses for its registers. Register values are never
+/// negative, so only unsigned types implement it.
+pub trait Register:
+  Copy
+  + Default
+  + Debug
+  + PartialEq
+  + BitXor<Output = Self>
+  + BitXorAssign
+  + TryFrom<u128>
+  + 'static
+{
+  fn from_u8(v: u8) -> Self;
+
+  /// The value modulo 8.
+  fn low3(self) -> u8;
+
+  /// The value divided by 2^n. Unlike `>>`, shifting by the register width or
+  /// more gives 0 instead of overflowing.
+  fn div_pow2(self, n: Self) -> Self;
+}
+
+macro_rules! impl_register {
+  ($t:ty) => {
+    impl Register for $t {
+      fn from_u8(v: u8) -> Self {
+        v as $t
+      }
+
+      fn low3(self) -> u8 {
+        (self & 0b111) as u8
+      }
+
+      fn div_pow2(self, n: Self) -> Self {
+        if n >= <$t>::BITS as $t {
+          0
+        } else {
+          self >> n
+        }
+      }
+    }
+  };
+}
+
+impl_register!(u64);
+impl_register!(u128);
+
+/// Initial register state.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Registers<R> {
+  pub a: R,
+  pub b: R,
+  pub c: R,
+}
+
+impl<R: Register> Registers<R> {
+  pub fn with_a(a: R) -> Registers<R> {
+    Registers {
+      a,
+      ..Default::default()
+    }
+  }
+}
+
+struct VM<R> {
+  a: R,
+  b: R,
+  c: R,
   i: usize,
   prog: Vec<i32>,
-  out: Vec<i32>,
+  out: Vec<u8>,
 }
 
-impl VM {
-  pub fn new(prog: &[i32], a: i64) -> VM {
+impl<R: Register> VM<R> {
+  pub fn new(prog: &[i32], regs: Registers<R>) -> VM<R> {
     VM {
-      a,
-      b: 0,
-      c: 0,
+      a: regs.a,
+      b: regs.b,
+      c: regs.c,
       i: 0,
       prog: prog.into(),
       out: Vec::new(),
@@ -31,46 +98,46 @@ impl VM {
       match op {
         0 => {
           // adv
-          self.a = self.a >> self.combo(operand);
+          self.a = self.a.div_pow2(self.combo(operand));
         }
         1 => {
           // bxl
-          self.b = self.b ^ (operand as i64);
+          self.b ^= R::from_u8(operand as u8);
         }
         2 => {
           // bst
-          self.b = self.combo(operand) % 8;
+          self.b = R::from_u8(self.combo(operand).low3());
         }
         3 => {
           // jnz
-          if self.a != 0 {
+          if self.a != R::default() {
             self.i = operand as usize;
           }
         }
         4 => {
           // bxc
-          self.b = self.b ^ self.c;
+          self.b ^= self.c;
         }
         5 => {
           // out
-          self.out.push((self.combo(operand) % 8) as i32);
+          self.out.push(self.combo(operand).low3());
         }
         6 => {
           // bdv
-          self.b = self.a >> self.combo(operand); 
+          self.b = self.a.div_pow2(self.combo(operand));
         }
         7 => {
           // cdv
-          self.c = self.a >> self.combo(operand); 
+          self.c = self.a.div_pow2(self.combo(operand));
         }
         _ => panic!(),
       }
     }
   }
 
-  fn combo(&self, operand: i32) -> i64 {
+  fn combo(&self, operand: i32) -> R {
     match operand {
-      0..=3 => operand as i64,
+      0..=3 => R::from_u8(operand as u8),
       4 => self.a,
       5 => self.b,
       6 => self.c,
@@ -79,8 +146,218 @@ impl VM {
   }
 }
 
-pub fn eval(prog: &[i32], a: i64) -> Vec<u8> {
-  let mut vm = VM::new(prog, a);
+pub fn eval<R: Register>(prog: &[i32], regs: Registers<R>) -> Vec<u8> {
+  let mut vm = VM::new(prog, regs);
   vm.run();
-  vm.out.into_iter().map(|e|e as u8).collect()
+  vm.out
+}
+
+// A compiled non-jump instruction.
+type Step<R> = Box<dyn Fn(&mut Registers<R>, &mut Vec<u8>)>;
+
+// A compiled instruction that also returns the next instruction pointer.
+type Instr<R> = Box<dyn Fn(&mut Registers<R>, &mut Vec<u8>) -> usize>;
+
+enum Body<R> {
+  // The program is a single loop: it ends with `jnz 0` and has no other
+  // jumps, so the body runs straight through with no instruction pointer.
+  Loop(Vec<Step<R>>),
+  // Anything else, with one instruction per address since jumps may land on
+  // odd addresses too.
+  Jumps(Vec<Instr<R>>),
+}
+
+/// A program translated ahead of time into a chain of closures. Opcode and
+/// operand decoding happen once in `compile` instead of on every step, and the
+/// output buffer is supplied by the caller so it can be reused between runs.
+pub struct Compiled<R> {
+  body: Body<R>,
+}
+
+impl<R: Register> Compiled<R> {
+  pub fn eval_into(&self, regs: Registers<R>, out: &mut Vec<u8>) {
+    out.clear();
+    let mut regs = regs;
+    match &self.body {
+      Body::Loop(steps) => loop {
+        for step in steps {
+          step(&mut regs, out);
+        }
+        if regs.a == R::default() {
+          break;
+        }
+      },
+      Body::Jumps(instrs) => {
+        let mut i = 0;
+        while i < instrs.len() {
+          i = instrs[i](&mut regs, out);
+        }
+      }
+    }
+  }
+
+  #[cfg(test)]
+  pub fn eval(&self, regs: Registers<R>) -> Vec<u8> {
+    let mut out = Vec::new();
+    self.eval_into(regs, &mut out);
+    out
+  }
+}
+
+pub fn compile<R: Register>(prog: &[i32]) -> Compiled<R> {
+  let body = if is_single_loop(prog) {
+    let n = prog.len() - 2;
+    Body::Loop(
+      (0..n)
+        .step_by(2)
+        .map(|i| compile_step(prog[i], prog[i + 1]))
+        .collect(),
+    )
+  } else {
+    Body::Jumps(
+      (0..prog.len().saturating_sub(1))
+        .map(|i| compile_instr(prog[i], prog[i + 1], i + 2))
+        .collect(),
+    )
+  };
+  Compiled { body }
+}
+
+fn is_single_loop(prog: &[i32]) -> bool {
+  prog.len() >= 2
+    && prog.len().is_multiple_of(2)
+    && prog.ends_with(&[3, 0])
+    && prog[..prog.len() - 2]
+      .chunks(2)
+      .all(|ins| (0..=7).contains(&ins[0]) && ins[0] != 3)
+}
+
+fn compile_instr<R: Register>(op: i32, operand: i32, next: usize) -> Instr<R> {
+  if op == 3 {
+    // jnz
+    let target = operand as usize;
+    return Box::new(
+      move |r, _| if r.a != R::default() { target } else { next },
+    );
+  }
+  let step = compile_step(op, operand);
+  Box::new(move |r, out| {
+    step(r, out);
+    next
+  })
+}
+
+fn compile_step<R: Register>(op: i32, operand: i32) -> Step<R> {
+  // Resolve the combo operand here so that each closure reads its register
+  // directly instead of going through a second dynamic call.
+  match operand {
+    0..=3 => build_step(op, operand, move |_| R::from_u8(operand as u8)),
+    4 => build_step(op, operand, |r| r.a),
+    5 => build_step(op, operand, |r| r.b),
+    6 => build_step(op, operand, |r| r.c),
+    // Only an error if the instruction actually reads it, same as `VM::combo`.
+    _ => {
+      build_step(op, operand, move |_| panic!("Invalid operand {}", operand))
+    }
+  }
+}
+
+fn build_step<R, C>(op: i32, operand: i32, combo: C) -> Step<R>
+where
+  R: Register,
+  C: Fn(&Registers<R>) -> R + 'static,
+{
+  match op {
+    0 => {
+      // adv
+      Box::new(move |r, _| r.a = r.a.div_pow2(combo(r)))
+    }
+    1 => {
+      // bxl
+      let lit = R::from_u8(operand as u8);
+      Box::new(move |r, _| r.b ^= lit)
+    }
+    2 => {
+      // bst
+      Box::new(move |r, _| r.b = R::from_u8(combo(r).low3()))
+    }
+    4 => {
+      // bxc
+      Box::new(|r, _| r.b ^= r.c)
+    }
+    5 => {
+      // out
+      Box::new(move |r, out| out.push(combo(r).low3()))
+    }
+    6 => {
+      // bdv
+      Box::new(move |r, _| r.b = r.a.div_pow2(combo(r)))
+    }
+    7 => {
+      // cdv
+      Box::new(move |r, _| r.c = r.a.div_pow2(combo(r)))
+    }
+    _ => Box::new(move |_, _| panic!("Invalid opcode {}", op)),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  const PROG: &[i32] = &[2, 4, 1, 5, 7, 5, 0, 3, 4, 0, 1, 6, 5, 5, 3, 0];
+
+  #[test]
+  fn initial_b_and_c() {
+    let regs = |b, c| Registers { a: 0u64, b, c };
+    // Examples from the puzzle, with an extra `out B` to observe B.
+    assert_eq!(eval(&[2, 6, 5, 5], regs(0, 9)), vec![1]);
+    assert_eq!(eval(&[1, 7, 5, 5], regs(29, 0)), vec![26 % 8]);
+    assert_eq!(
+      eval(&[4, 0, 5, 5], regs(2024, 43690)),
+      vec![(44354 % 8) as u8]
+    );
+  }
+
+  #[test]
+  fn wide_registers() {
+    // Prints A in octal, least significant digit first.
+    let prog = &[5, 4, 0, 3, 3, 0];
+    let a: u128 = 0o1234567012345670123456701234567012345;
+    let mut digits = eval(prog, Registers::with_a(a));
+    digits.reverse();
+    let s: String = digits.iter().map(|d| d.to_string()).collect();
+    assert_eq!(s, "1234567012345670123456701234567012345");
+    // Shifting by the register width or more clears it instead of overflowing.
+    assert_eq!(eval(&[0, 4, 5, 4], Registers::with_a(200u64)), vec![0]);
+  }
+
+  #[test]
+  fn compiled_matches_vm() {
+    let compiled = compile(PROG);
+    let mut out = Vec::new();
+    for a in (0..100_000u64).chain([46187030, 1 << 40]) {
+      let regs = Registers::with_a(a);
+      compiled.eval_into(regs, &mut out);
+      assert_eq!(out, eval(PROG, regs), "a={}", a);
+    }
+    let regs = Registers::with_a(u128::MAX - 12345);
+    assert_eq!(compile(PROG).eval(regs), eval(PROG, regs));
+  }
+
+  #[test]
+  fn compiled_jumps_match_vm() {
+    // Jumps into the middle of the program and onto an odd address.
+    for prog in [&[1, 3, 0, 1, 5, 4, 3, 2][..], &[5, 4, 4, 0, 3, 3, 0, 5]] {
+      assert!(!is_single_loop(prog));
+      for a in 0..1000u64 {
+        let regs = Registers {
+          a,
+          b: a / 3,
+          c: a / 7,
+        };
+        assert_eq!(compile(prog).eval(regs), eval(prog, regs), "a={}", a);
+      }
+    }
+  }
 }
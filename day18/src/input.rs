@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct V(pub i32, pub i32);
 
@@ -26,6 +28,143 @@ impl std::ops::Sub for V {
   }
 }
 
+impl std::str::FromStr for V {
+  type Err = String;
+
+  // Parses "x,y".
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (x, y) = s
+      .trim()
+      .split_once(',')
+      .ok_or_else(|| format!("expected x,y but got {:?}", s))?;
+    match (x.trim().parse(), y.trim().parse()) {
+      (Ok(x), Ok(y)) => Ok(V(x, y)),
+      _ => Err(format!("invalid coordinate {:?}", s)),
+    }
+  }
+}
+
+/// The memory space bytes fall into: its size and where the route starts
+/// and ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+  pub width: i32,
+  pub height: i32,
+  pub start: V,
+  pub goal: V,
+}
+
+impl Layout {
+  /// A `width` x `height` space going from the top left corner to the
+  /// bottom right one.
+  pub fn new(width: i32, height: i32) -> Layout {
+    Layout {
+      width,
+      height,
+      start: V(0, 0),
+      goal: V(width - 1, height - 1),
+    }
+  }
+
+  /// How many bytes have fallen for part 1 in the puzzle: 12 in the 7x7
+  /// sample, 1024 in the real 71x71 space.
+  pub fn default_byte_count(&self) -> usize {
+    if (self.width, self.height) == (7, 7) {
+      12
+    } else {
+      1024
+    }
+  }
+
+  pub fn contains(&self, pos: V) -> bool {
+    pos.x() >= 0
+      && pos.x() < self.width
+      && pos.y() >= 0
+      && pos.y() < self.height
+  }
+
+  /// Index of `pos` in a row-major vector of the space, or None if it's
+  /// outside.
+  pub fn cell(&self, pos: V) -> Option<usize> {
+    if !self.contains(pos) {
+      return None;
+    }
+    Some((pos.y() * self.width + pos.x()) as usize)
+  }
+}
+
+/// A byte that doesn't fit the memory space. Lines are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteIssue {
+  OutOfRange {
+    line: usize,
+    pos: V,
+    width: i32,
+    height: i32,
+  },
+  Duplicate {
+    line: usize,
+    pos: V,
+    first_line: usize,
+  },
+}
+
+impl std::fmt::Display for ByteIssue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ByteIssue::OutOfRange {
+        line,
+        pos,
+        width,
+        height,
+      } => write!(
+        f,
+        "{}: byte {},{} is outside the {}x{} space",
+        line,
+        pos.x(),
+        pos.y(),
+        width,
+        height
+      ),
+      ByteIssue::Duplicate {
+        line,
+        pos,
+        first_line,
+      } => write!(
+        f,
+        "{}: byte {},{} already fell on line {}",
+        line,
+        pos.x(),
+        pos.y(),
+        first_line
+      ),
+    }
+  }
+}
+
+impl std::error::Error for ByteIssue {}
+
+/// What to do with bytes that don't fit the memory space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnInvalid {
+  /// Fail validation.
+  Reject,
+  /// Drop them and carry on with the rest.
+  Ignore,
+}
+
+impl std::str::FromStr for OnInvalid {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "reject" => Ok(OnInvalid::Reject),
+      "ignore" => Ok(OnInvalid::Ignore),
+      _ => Err(format!("expected reject or ignore but got {:?}", s)),
+    }
+  }
+}
+
 pub struct Input {
   pub coords: Vec<V>,
 }
@@ -46,6 +185,138 @@ impl Input {
       .collect::<Option<Vec<V>>>()
       .unwrap();
 
-    Input {coords}
+    Input { coords }
+  }
+
+  /// The smallest width and height that fit every coordinate, or None if
+  /// there are none.
+  pub fn grid_size(&self) -> Option<(i32, i32)> {
+    let width = self.coords.iter().map(|c| c.x()).max()? + 1;
+    let height = self.coords.iter().map(|c| c.y()).max()? + 1;
+    Some((width, height))
+  }
+
+  /// Finds bytes that fall outside `layout` or on a byte that already fell.
+  /// Coordinate i comes from line i + 1.
+  pub fn check(&self, layout: &Layout) -> Vec<ByteIssue> {
+    let mut first_line: HashMap<V, usize> = HashMap::new();
+    let mut issues = Vec::new();
+    for (i, &pos) in self.coords.iter().enumerate() {
+      let line = i + 1;
+      if !layout.contains(pos) {
+        issues.push(ByteIssue::OutOfRange {
+          line,
+          pos,
+          width: layout.width,
+          height: layout.height,
+        });
+      } else if let Some(&first_line) = first_line.get(&pos) {
+        issues.push(ByteIssue::Duplicate {
+          line,
+          pos,
+          first_line,
+        });
+      } else {
+        first_line.insert(pos, line);
+      }
+    }
+    issues
+  }
+
+  /// Checks the input against `layout`. With `OnInvalid::Reject` any issue
+  /// fails the whole input; with `OnInvalid::Ignore` the offending bytes are
+  /// dropped and the issues returned alongside the rest.
+  pub fn validate(
+    self,
+    layout: &Layout,
+    on_invalid: OnInvalid,
+  ) -> Result<(Input, Vec<ByteIssue>), Vec<ByteIssue>> {
+    let issues = self.check(layout);
+    if issues.is_empty() {
+      return Ok((self, issues));
+    }
+    if on_invalid == OnInvalid::Reject {
+      return Err(issues);
+    }
+
+    let mut skip = vec![false; self.coords.len()];
+    for issue in &issues {
+      match issue {
+        ByteIssue::OutOfRange { line, .. }
+        | ByteIssue::Duplicate { line, .. } => skip[line - 1] = true,
+      }
+    }
+    let coords = self
+      .coords
+      .into_iter()
+      .zip(skip)
+      .filter(|&(_, skip)| !skip)
+      .map(|(pos, _)| pos)
+      .collect();
+    Ok((Input { coords }, issues))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn grid_size() {
+    let input = Input::parse("5,4\n4,2\n0,6\n");
+    assert_eq!(input.grid_size(), Some((6, 7)));
+    assert_eq!(Input::parse("").grid_size(), None);
+    assert_eq!(Layout::new(7, 7).default_byte_count(), 12);
+    assert_eq!(Layout::new(71, 71).default_byte_count(), 1024);
+  }
+
+  #[test]
+  fn validate() {
+    let layout = Layout::new(3, 3);
+    let input = "0,1\n3,0\n1,1\n0,1\n1,-1\n2,2\n";
+    let issues = Input::parse(input).check(&layout);
+    assert_eq!(
+      issues,
+      vec![
+        ByteIssue::OutOfRange {
+          line: 2,
+          pos: V(3, 0),
+          width: 3,
+          height: 3
+        },
+        ByteIssue::Duplicate {
+          line: 4,
+          pos: V(0, 1),
+          first_line: 1
+        },
+        ByteIssue::OutOfRange {
+          line: 5,
+          pos: V(1, -1),
+          width: 3,
+          height: 3
+        },
+      ]
+    );
+    assert_eq!(
+      issues[0].to_string(),
+      "2: byte 3,0 is outside the 3x3 space"
+    );
+    assert_eq!(issues[1].to_string(), "4: byte 0,1 already fell on line 1");
+
+    let rejected = Input::parse(input).validate(&layout, OnInvalid::Reject);
+    assert_eq!(rejected.err(), Some(issues.clone()));
+
+    let (input, ignored) = Input::parse(input)
+      .validate(&layout, OnInvalid::Ignore)
+      .unwrap();
+    assert_eq!(ignored, issues);
+    assert_eq!(input.coords, vec![V(0, 1), V(1, 1), V(2, 2)]);
+  }
+
+  #[test]
+  fn parse_v() {
+    assert_eq!("3, 4".parse::<V>(), Ok(V(3, 4)));
+    assert!("3".parse::<V>().is_err());
+    assert!("a,4".parse::<V>().is_err());
   }
 }
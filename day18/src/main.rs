@@ -1,9 +1,91 @@
 mod input;
 mod part1;
 mod part2;
+mod timed;
+
+use input::{Input, Layout, OnInvalid, V};
+use part2::Strategy;
+
+const USAGE: &str = "usage: day18 [filename] [--width <n>] [--height <n>] \
+  [--bytes <n>] [--start <x,y>] [--goal <x,y>] [--strategy <name>] [--timed] \
+  [--invalid reject|ignore]\n\
+  --bytes defaults to 12 on a 7x7 grid, like the sample, and 1024 otherwise";
 
 fn main() {
-  let input = std::fs::read_to_string("input.txt").unwrap();
-  println!("part1={}", part1::eval(&input, 71, 71, 1024).unwrap());
-  println!("part2={:?}", part2::eval(&input, 71, 71).unwrap());
+  let mut filename = "input.txt".to_string();
+  let mut width: Option<i32> = None;
+  let mut height: Option<i32> = None;
+  let mut byte_count: Option<usize> = None;
+  let mut start: Option<V> = None;
+  let mut goal: Option<V> = None;
+  let mut strategy = Strategy::Search;
+  let mut timed = false;
+  let mut on_invalid = OnInvalid::Reject;
+
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    let mut value = || {
+      args
+        .next()
+        .unwrap_or_else(|| panic!("{} needs a value\n{}", arg, USAGE))
+    };
+    match arg.as_str() {
+      "--width" => width = Some(value().parse().unwrap()),
+      "--height" => height = Some(value().parse().unwrap()),
+      "--bytes" => byte_count = Some(value().parse().unwrap()),
+      "--start" => start = Some(value().parse().unwrap()),
+      "--goal" => goal = Some(value().parse().unwrap()),
+      "--strategy" => strategy = value().parse().unwrap(),
+      "--timed" => timed = true,
+      "--invalid" => on_invalid = value().parse().unwrap(),
+      _ if !arg.starts_with("--") => filename = arg,
+      _ => panic!("unexpected argument {:?}\n{}", arg, USAGE),
+    }
+  }
+
+  let input = Input::parse(&std::fs::read_to_string(&filename).unwrap());
+
+  // Anything not given on the command line is sized to fit the input.
+  let (fit_width, fit_height) = input.grid_size().unwrap_or((1, 1));
+  let mut layout =
+    Layout::new(width.unwrap_or(fit_width), height.unwrap_or(fit_height));
+  layout.start = start.unwrap_or(layout.start);
+  layout.goal = goal.unwrap_or(layout.goal);
+  let byte_count = byte_count.unwrap_or(layout.default_byte_count());
+  assert!(
+    layout.contains(layout.start) && layout.contains(layout.goal),
+    "start and goal must be within {}x{}",
+    layout.width,
+    layout.height
+  );
+
+  let input = match input.validate(&layout, on_invalid) {
+    Ok((input, ignored)) => {
+      for issue in ignored {
+        eprintln!("{}:{} (ignored)", filename, issue);
+      }
+      input
+    }
+    Err(issues) => {
+      for issue in issues {
+        eprintln!("{}:{}", filename, issue);
+      }
+      std::process::exit(1);
+    }
+  };
+
+  match part1::eval(&input, &layout, byte_count) {
+    Some(steps) => println!("part1={}", steps),
+    None => println!("part1: no route"),
+  }
+  match part2::eval(&input, &layout, strategy) {
+    Some(pos) => println!("part2={:?}", pos),
+    None => println!("part2: never blocked"),
+  }
+  if timed {
+    match timed::eval(&input, &layout, byte_count) {
+      Some(steps) => println!("timed={}", steps),
+      None => println!("timed: no route"),
+    }
+  }
 }
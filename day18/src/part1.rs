@@ -1,10 +1,9 @@
 use std::collections::{BinaryHeap, HashSet};
 
-use crate::input::{Input, V};
+use crate::input::{Input, Layout, V};
 
-struct Grid {
-  pub width: i32,
-  pub height: i32,
+pub struct Grid {
+  pub layout: Layout,
   pub walls: HashSet<V>,
 }
 
@@ -16,11 +15,7 @@ struct Path {
 impl Path {
   pub fn step(&self, dir: V, grid: &Grid) -> Option<Path> {
     let pos = self.pos + dir;
-    if pos.x() < 0
-      || pos.x() >= grid.width
-      || pos.y() < 0
-      || pos.y() >= grid.height
-    {
+    if !grid.layout.contains(pos) {
       return None;
     }
 
@@ -67,9 +62,12 @@ struct Search<'a> {
 
 impl<'a> Search<'a> {
   pub fn search(grid: &'a Grid) -> Option<Path> {
-    assert!(grid.width > 0 && grid.height > 0);
-    let start = V(0, 0);
-    let goal = V(grid.width - 1, grid.height - 1);
+    assert!(grid.layout.width > 0 && grid.layout.height > 0);
+    let start = grid.layout.start;
+    let goal = grid.layout.goal;
+    if grid.walls.contains(&start) {
+      return None;
+    }
 
     let mut s = Search {
       grid,
@@ -127,10 +125,10 @@ impl<'a> Search<'a> {
 
 #[allow(dead_code)]
 fn dump_path(path: &Path, grid: &Grid) {
-  let mut grid: Vec<Vec<char>> = (0..grid.height)
+  let mut grid: Vec<Vec<char>> = (0..grid.layout.height)
     .map(|j| {
-      (0..grid.width)
-        .map(move |i| match grid.walls.get(&V(i as i32, j as i32)) {
+      (0..grid.layout.width)
+        .map(move |i| match grid.walls.get(&V(i, j)) {
           Some(_) => '#',
           None => '.',
         })
@@ -147,24 +145,12 @@ fn dump_path(path: &Path, grid: &Grid) {
   }
 }
 
-pub fn eval(
-  input: &str,
-  width: i32,
-  height: i32,
-  byte_count: i32,
-) -> Option<i32> {
-  let input = Input::parse(input);
-
-  let walls: HashSet<V> = input
-    .coords
-    .iter()
-    .copied()
-    .take(byte_count as usize)
-    .collect();
+pub fn eval(input: &Input, layout: &Layout, byte_count: usize) -> Option<i32> {
+  let walls: HashSet<V> =
+    input.coords.iter().copied().take(byte_count).collect();
 
   let grid = Grid {
-    width,
-    height,
+    layout: *layout,
     walls,
   };
 
@@ -172,7 +158,7 @@ pub fn eval(
 
   // The -1 is because the problem is looking for the number of steps, not the
   // number of positions.
-  path.and_then(|p| Some((p.path.len() as i32) - 1))
+  path.map(|p| (p.path.len() as i32) - 1)
 }
 
 #[cfg(test)]
@@ -207,6 +193,18 @@ mod test {
 1,6
 2,0
 ";
-    assert_eq!(eval(input, 7, 7, 12).unwrap(), 22);
+    let input = Input::parse(input);
+    assert_eq!(eval(&input, &Layout::new(7, 7), 12).unwrap(), 22);
+    let layout = Layout {
+      start: V(6, 6),
+      goal: V(0, 0),
+      ..Layout::new(7, 7)
+    };
+    assert_eq!(eval(&input, &layout, 12).unwrap(), 22);
+    let layout = Layout {
+      goal: V(6, 0),
+      ..Layout::new(7, 7)
+    };
+    assert_eq!(eval(&input, &layout, 12).unwrap(), 10);
   }
 }
@@ -1,10 +1,13 @@
-use std::{collections::{BinaryHeap, HashSet}, iter};
+use std::{
+  collections::{BinaryHeap, HashSet, VecDeque},
+  iter,
+  str::FromStr,
+};
 
-use crate::input::{Input, V};
+use crate::input::{Input, Layout, V};
 
 struct Grid {
-  pub width: i32,
-  pub height: i32,
+  pub layout: Layout,
   pub walls: HashSet<V>,
 }
 
@@ -16,11 +19,7 @@ struct Path {
 impl Path {
   pub fn step(&self, dir: V, grid: &Grid) -> Option<Path> {
     let pos = self.pos + dir;
-    if pos.x() < 0
-      || pos.x() >= grid.width
-      || pos.y() < 0
-      || pos.y() >= grid.height
-    {
+    if !grid.layout.contains(pos) {
       return None;
     }
 
@@ -67,9 +66,12 @@ struct Search<'a> {
 
 impl<'a> Search<'a> {
   pub fn search(grid: &'a Grid) -> Option<Path> {
-    assert!(grid.width > 0 && grid.height > 0);
-    let start = V(0, 0);
-    let goal = V(grid.width - 1, grid.height - 1);
+    assert!(grid.layout.width > 0 && grid.layout.height > 0);
+    let start = grid.layout.start;
+    let goal = grid.layout.goal;
+    if grid.walls.contains(&start) {
+      return None;
+    }
 
     let mut s = Search {
       grid,
@@ -127,10 +129,10 @@ impl<'a> Search<'a> {
 
 #[allow(dead_code)]
 fn dump_path(path: &Path, grid: &Grid) {
-  let mut grid: Vec<Vec<char>> = (0..grid.height)
+  let mut grid: Vec<Vec<char>> = (0..grid.layout.height)
     .map(|j| {
-      (0..grid.width)
-        .map(move |i| match grid.walls.get(&V(i as i32, j as i32)) {
+      (0..grid.layout.width)
+        .map(move |i| match grid.walls.get(&V(i, j)) {
           Some(_) => '#',
           None => '.',
         })
@@ -147,26 +149,54 @@ fn dump_path(path: &Path, grid: &Grid) {
   }
 }
 
-pub fn eval(
-  input: &str,
-  width: i32,
-  height: i32,
-) -> Option<V> {
-  let input = Input::parse(input);
+/// How to find the first byte that cuts off the exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+  /// Drop bytes one at a time and search for a new path whenever one lands
+  /// on the current path.
+  Search,
+  /// Binary search over the number of fallen bytes, checking whether the
+  /// exit is reachable with a BFS.
+  Bisect,
+  /// Drop every byte, then take them back in reverse order, joining the free
+  /// regions with union-find until the start and exit connect.
+  UnionFind,
+}
+
+impl FromStr for Strategy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "search" => Ok(Strategy::Search),
+      "bisect" => Ok(Strategy::Bisect),
+      "union-find" => Ok(Strategy::UnionFind),
+      _ => Err(format!("unknown strategy {:?}", s)),
+    }
+  }
+}
+
+pub fn eval(input: &Input, layout: &Layout, strategy: Strategy) -> Option<V> {
+  match strategy {
+    Strategy::Search => eval_search(&input.coords, layout),
+    Strategy::Bisect => eval_bisect(&input.coords, layout),
+    Strategy::UnionFind => eval_union_find(&input.coords, layout),
+  }
+}
 
+fn eval_search(coords: &[V], layout: &Layout) -> Option<V> {
   let mut grid = Grid {
-    width,
-    height,
+    layout: *layout,
     walls: HashSet::new(),
   };
 
   let mut path: Option<Path> = None;
 
-  for coord in input.coords {
+  for &coord in coords {
     grid.walls.insert(coord);
 
     if let Some(path) = &path {
-      if !path.path.contains(&coord){
+      if !path.path.contains(&coord) {
         continue;
       }
     }
@@ -182,6 +212,150 @@ pub fn eval(
   None
 }
 
+fn eval_bisect(coords: &[V], layout: &Layout) -> Option<V> {
+  let is_blocked = |count: usize| {
+    let mut walls = vec![false; (layout.width * layout.height) as usize];
+    for &coord in &coords[..count] {
+      if let Some(i) = layout.cell(coord) {
+        walls[i] = true;
+      }
+    }
+    !is_reachable(&walls, layout)
+  };
+
+  if !is_blocked(coords.len()) {
+    return None;
+  }
+  // Invariant: the first `lo` bytes leave a way out, the first `hi` don't.
+  let (mut lo, mut hi) = (0, coords.len());
+  while hi - lo > 1 {
+    let mid = (lo + hi) / 2;
+    if is_blocked(mid) {
+      hi = mid;
+    } else {
+      lo = mid;
+    }
+  }
+  Some(coords[hi - 1])
+}
+
+// BFS from the start to the goal.
+fn is_reachable(walls: &[bool], layout: &Layout) -> bool {
+  let (start, goal) = (layout.start, layout.goal);
+  let mut seen = walls.to_vec();
+  match layout.cell(start) {
+    Some(i) if !seen[i] => seen[i] = true,
+    _ => return false,
+  }
+
+  let mut queue = VecDeque::from([start]);
+  while let Some(pos) = queue.pop_front() {
+    if pos == goal {
+      return true;
+    }
+    for dir in [V(0, -1), V(0, 1), V(1, 0), V(-1, 0)] {
+      let next = pos + dir;
+      if let Some(i) = layout.cell(next) {
+        if !seen[i] {
+          seen[i] = true;
+          queue.push_back(next);
+        }
+      }
+    }
+  }
+  false
+}
+
+struct UnionFind {
+  parent: Vec<usize>,
+  size: Vec<usize>,
+}
+
+impl UnionFind {
+  pub fn new(n: usize) -> UnionFind {
+    UnionFind {
+      parent: (0..n).collect(),
+      size: vec![1; n],
+    }
+  }
+
+  pub fn find(&mut self, mut i: usize) -> usize {
+    while self.parent[i] != i {
+      self.parent[i] = self.parent[self.parent[i]];
+      i = self.parent[i];
+    }
+    i
+  }
+
+  pub fn union(&mut self, a: usize, b: usize) {
+    let (mut a, mut b) = (self.find(a), self.find(b));
+    if a == b {
+      return;
+    }
+    if self.size[a] < self.size[b] {
+      std::mem::swap(&mut a, &mut b);
+    }
+    self.parent[b] = a;
+    self.size[a] += self.size[b];
+  }
+}
+
+fn eval_union_find(coords: &[V], layout: &Layout) -> Option<V> {
+  let n = (layout.width * layout.height) as usize;
+  let start = layout.cell(layout.start)?;
+  let goal = layout.cell(layout.goal)?;
+
+  // A cell only becomes free again once the first byte that fell on it is
+  // taken back.
+  let mut first_byte: Vec<Option<usize>> = vec![None; n];
+  for (b, &coord) in coords.iter().enumerate() {
+    if let Some(i) = layout.cell(coord) {
+      first_byte[i].get_or_insert(b);
+    }
+  }
+  let mut free: Vec<bool> = first_byte.iter().map(|b| b.is_none()).collect();
+
+  let mut uf = UnionFind::new(n);
+  let join = |uf: &mut UnionFind, free: &[bool], pos: V| {
+    let i = layout.cell(pos).unwrap();
+    for dir in [V(0, -1), V(0, 1), V(1, 0), V(-1, 0)] {
+      if let Some(j) = layout.cell(pos + dir) {
+        if free[j] {
+          uf.union(i, j);
+        }
+      }
+    }
+  };
+  for y in 0..layout.height {
+    for x in 0..layout.width {
+      if free[layout.cell(V(x, y)).unwrap()] {
+        join(&mut uf, &free, V(x, y));
+      }
+    }
+  }
+
+  let connected = |uf: &mut UnionFind, free: &[bool]| {
+    free[start] && free[goal] && uf.find(start) == uf.find(goal)
+  };
+  if connected(&mut uf, &free) {
+    return None;
+  }
+  for (b, &coord) in coords.iter().enumerate().rev() {
+    let Some(i) = layout.cell(coord) else {
+      continue;
+    };
+    if first_byte[i] != Some(b) {
+      continue;
+    }
+    free[i] = true;
+    join(&mut uf, &free, coord);
+    if connected(&mut uf, &free) {
+      return Some(coord);
+    }
+  }
+  None
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -214,6 +388,41 @@ mod test {
 1,6
 2,0
 ";
-    assert_eq!(eval(input, 7, 7).unwrap(), V(6,1));
+    let input = Input::parse(input);
+    for strategy in [Strategy::Search, Strategy::Bisect, Strategy::UnionFind] {
+      assert_eq!(eval(&input, &Layout::new(7, 7), strategy), Some(V(6, 1)));
+    }
+  }
+
+  #[test]
+  fn strategies_agree() {
+    // Pseudo-random bytes, with repeats and some landing on the start or
+    // exit.
+    let mut seed: u32 = 12345;
+    let mut next = |n: u32| {
+      seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+      ((seed >> 16) % n) as i32
+    };
+    for size in [3, 5, 10, 20] {
+      let coords: Vec<V> = (0..size * size)
+        .map(|_| V(next(size as u32), next(size as u32)))
+        .collect();
+      let layouts = [
+        Layout::new(size, size),
+        Layout {
+          start: V(size / 2, size / 2),
+          goal: V(0, size - 1),
+          ..Layout::new(size, size)
+        },
+      ];
+      for layout in &layouts {
+        for count in [0, 1, coords.len() / 2, coords.len()] {
+          let coords = &coords[..count];
+          let expected = eval_search(coords, layout);
+          assert_eq!(eval_bisect(coords, layout), expected);
+          assert_eq!(eval_union_find(coords, layout), expected);
+        }
+      }
+    }
   }
 }
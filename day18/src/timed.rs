@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::input::{Input, Layout, V};
+use crate::part1::Grid;
+
+/// Finds the fewest steps from start to goal when `byte_count` bytes have
+/// already fallen and one more falls after every step. You can't step onto
+/// a byte, and you can't be where a byte lands. Returns None if every route
+/// gets cut off.
+pub fn eval(
+  input: &Input,
+  layout: &Layout,
+  byte_count: usize,
+) -> Option<usize> {
+  let grid = Grid {
+    layout: *layout,
+    walls: input.coords.iter().copied().take(byte_count).collect(),
+  };
+
+  // When each remaining byte falls, counted in steps taken. Only the first
+  // byte to land on a cell matters.
+  let mut falls_at: HashMap<V, usize> = HashMap::new();
+  for (t, &coord) in input.coords.iter().skip(byte_count).enumerate() {
+    if !grid.walls.contains(&coord) {
+      falls_at.entry(coord).or_insert(t + 1);
+    }
+  }
+  let is_blocked = |pos: V, t: usize| {
+    grid.walls.contains(&pos) || falls_at.get(&pos).is_some_and(|&f| f <= t)
+  };
+
+  // BFS over (pos, t). Walls only ever grow, so reaching a position later
+  // can't open up anything that reaching it earlier didn't; each position
+  // needs to be visited once, and waiting in place never helps.
+  let start = layout.start;
+  if !layout.contains(start) || is_blocked(start, 0) {
+    return None;
+  }
+  let mut visited: HashSet<V> = HashSet::from([start]);
+  let mut queue = VecDeque::from([(start, 0)]);
+  while let Some((pos, t)) = queue.pop_front() {
+    if pos == layout.goal {
+      return Some(t);
+    }
+    for dir in [V(0, -1), V(0, 1), V(1, 0), V(-1, 0)] {
+      let next = pos + dir;
+      if layout.contains(next)
+        && !is_blocked(next, t + 1)
+        && visited.insert(next)
+      {
+        queue.push_back((next, t + 1));
+      }
+    }
+  }
+
+  None
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn falling_bytes() {
+    let layout = Layout::new(3, 1);
+    // The byte lands on the goal before we get there.
+    assert_eq!(eval(&Input::parse("2,0\n"), &layout, 0), None);
+    // The byte lands where we were.
+    assert_eq!(eval(&Input::parse("0,0\n"), &layout, 0), Some(2));
+    // Already fallen.
+    assert_eq!(eval(&Input::parse("0,0\n"), &layout, 1), None);
+  }
+
+  #[test]
+  fn detour() {
+    //  S..
+    //  .#.
+    //  ..E
+    let layout = Layout::new(3, 3);
+    // The way right closes before we can take it, the way down stays open.
+    assert_eq!(eval(&Input::parse("1,1\n1,0\n"), &layout, 1), Some(4));
+    // Both close just in time.
+    assert_eq!(eval(&Input::parse("1,1\n1,0\n0,2\n"), &layout, 1), None);
+  }
+
+  #[test]
+  fn sample() {
+    let input = Input::parse(include_str!("../sample_input.txt"));
+    let layout = Layout::new(7, 7);
+    assert_eq!(eval(&input, &layout, 0), Some(12));
+    assert_eq!(eval(&input, &layout, 8), Some(22));
+    // With 12 bytes down there's a 22 step route through a static space,
+    // but the next bytes close it before we get through.
+    assert_eq!(eval(&input, &layout, 12), None);
+  }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use crate::count::Count;
+use crate::towel_trie::Color;
+use crate::trie::Dense;
+
+const ROOT: usize = 0;
+
+/// An Aho-Corasick automaton over the towels, used to find every towel that
+/// ends at each position of a design in a single pass.
+pub struct Automaton {
+  // Full transition table: every state has a move for every color, with
+  // failure links already folded in.
+  next: Vec<[u32; Color::COUNT]>,
+  // Lengths of the towels that end at each state, including those reached
+  // through failure links.
+  ends: Vec<Vec<usize>>,
+}
+
+impl Automaton {
+  pub fn new<'a, I>(towels: I) -> Automaton
+  where
+    I: IntoIterator<Item = &'a [Color]>,
+  {
+    // Build the trie, with 0 standing for a missing edge. The root is never
+    // a child, so there's no ambiguity.
+    let mut next = vec![[0u32; Color::COUNT]];
+    let mut ends: Vec<Vec<usize>> = vec![Vec::new()];
+    for towel in towels {
+      let mut state = ROOT;
+      for &c in towel {
+        if next[state][c.index()] == 0 {
+          next[state][c.index()] = next.len() as u32;
+          next.push([0; Color::COUNT]);
+          ends.push(Vec::new());
+        }
+        state = next[state][c.index()] as usize;
+      }
+      if !ends[state].contains(&towel.len()) {
+        ends[state].push(towel.len());
+      }
+    }
+
+    // Breadth first, so a state's failure link is final before its children
+    // are visited.
+    let mut fail = vec![ROOT; next.len()];
+    let mut queue: VecDeque<usize> = next[ROOT]
+      .iter()
+      .map(|&n| n as usize)
+      .filter(|&n| n != ROOT)
+      .collect();
+    while let Some(state) = queue.pop_front() {
+      let inherited = ends[fail[state]].clone();
+      ends[state].extend(inherited);
+      let fallback = next[fail[state]];
+      for (child, fallback) in next[state].iter_mut().zip(fallback) {
+        if *child as usize == ROOT {
+          *child = fallback;
+        } else {
+          fail[*child as usize] = fallback as usize;
+          queue.push_back(*child as usize);
+        }
+      }
+    }
+
+    Automaton { next, ends }
+  }
+
+  /// Number of ways to arrange towels into `design`, counting in the type
+  /// of `one`, or None if the count overflows it. `ways[i]` counts the
+  /// arrangements of the first i colors; every towel ending at i extends
+  /// the arrangements of what comes before it.
+  pub fn count<C: Count>(&self, design: &[Color], one: &C) -> Option<C> {
+    let mut ways = vec![one.zero_like(); design.len() + 1];
+    ways[0] = one.clone();
+    let mut state = ROOT;
+    for (i, &c) in design.iter().enumerate() {
+      state = self.next[state][c.index()] as usize;
+      let end = i + 1;
+      for &len in &self.ends[state] {
+        ways[end] = ways[end].checked_add(&ways[end - len])?;
+      }
+    }
+    ways.pop()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn colors(s: &str) -> Vec<Color> {
+    Color::from_str(s).unwrap()
+  }
+
+  #[test]
+  fn sample() {
+    let towels: Vec<Vec<Color>> =
+      ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"]
+        .iter()
+        .map(|t| colors(t))
+        .collect();
+    let automaton = Automaton::new(towels.iter().map(Vec::as_slice));
+    let counts: Vec<u64> = [
+      "brwrr", "bggr", "gbbr", "rrbgbr", "ubwu", "bwurrg", "brgr", "bbrgwb",
+    ]
+    .iter()
+    .map(|d| automaton.count(&colors(d), &1u64).unwrap())
+    .collect();
+    assert_eq!(counts, vec![2, 1, 4, 6, 0, 1, 2, 0]);
+  }
+
+  #[test]
+  fn overlapping_towels() {
+    // "ub" is only found through the failure link out of "wu".
+    let towels = [
+      colors("w"),
+      colors("wu"),
+      colors("ub"),
+      colors("b"),
+      colors("wub"),
+    ];
+    let automaton = Automaton::new(towels.iter().map(Vec::as_slice));
+    // w+ub, wu+b, wub
+    assert_eq!(automaton.count(&colors("wub"), &1u64), Some(3));
+    assert_eq!(automaton.count(&colors("u"), &1u64), Some(0));
+    assert_eq!(automaton.count(&[], &1u64), Some(1));
+  }
+}
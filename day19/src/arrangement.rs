@@ -0,0 +1,180 @@
+use crate::towel_trie::{Color, TowelTrie};
+
+// For every offset into a design, the ends of the towels that can start
+// there and still leave a suffix that can be made, and the fewest towels
+// needed from there to the end.
+struct Choices {
+  ends: Vec<Vec<usize>>,
+  fewest: Vec<Option<usize>>,
+}
+
+impl Choices {
+  fn new(design: &[Color], towels: &TowelTrie) -> Choices {
+    let n = design.len();
+    let mut ends = vec![Vec::new(); n + 1];
+    let mut fewest = vec![None; n + 1];
+    fewest[n] = Some(0);
+    for i in (0..n).rev() {
+      ends[i] = towels
+        .prefixes(&design[i..])
+        .map(|len| i + len)
+        .filter(|&end| fewest[end].is_some())
+        .collect();
+      fewest[i] = ends[i]
+        .iter()
+        .filter_map(|&end| fewest[end])
+        .min()
+        .map(|f| f + 1);
+    }
+    Choices { ends, fewest }
+  }
+}
+
+/// Every way to arrange towels into a design, one at a time. Dead ends are
+/// pruned up front, so each arrangement takes time proportional to the
+/// design's length, however many there are in total.
+pub struct Arrangements<'a> {
+  design: &'a str,
+  choices: Choices,
+  // The arrangement last returned: each towel's start and which of the ends
+  // at that start it uses.
+  frames: Vec<(usize, usize)>,
+  started: bool,
+}
+
+impl<'a> Arrangements<'a> {
+  pub fn new(design: &'a str, towels: &TowelTrie) -> Arrangements<'a> {
+    let colors = Color::from_str(design).unwrap();
+    Arrangements {
+      design,
+      choices: Choices::new(&colors, towels),
+      frames: Vec::new(),
+      started: false,
+    }
+  }
+
+  // Extends the arrangement from `pos` to the end of the design, taking the
+  // first choice at each step.
+  fn descend(&mut self, mut pos: usize) {
+    while pos < self.design.len() {
+      self.frames.push((pos, 0));
+      pos = self.choices.ends[pos][0];
+    }
+  }
+
+  fn current(&self) -> Vec<&'a str> {
+    self
+      .frames
+      .iter()
+      .map(|&(start, k)| &self.design[start..self.choices.ends[start][k]])
+      .collect()
+  }
+}
+
+impl<'a> Iterator for Arrangements<'a> {
+  type Item = Vec<&'a str>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if !self.started {
+      self.started = true;
+      self.choices.fewest[0]?;
+      self.descend(0);
+      return Some(self.current());
+    }
+
+    while let Some((start, k)) = self.frames.pop() {
+      if let Some(&end) = self.choices.ends[start].get(k + 1) {
+        self.frames.push((start, k + 1));
+        self.descend(end);
+        return Some(self.current());
+      }
+    }
+    None
+  }
+}
+
+/// An arrangement of `design` using as few towels as possible, or None if
+/// it can't be made.
+pub fn shortest(design: &str, towels: &TowelTrie) -> Option<Vec<String>> {
+  let colors = Color::from_str(design).unwrap();
+  let choices = Choices::new(&colors, towels);
+  choices.fewest[0]?;
+
+  let mut arrangement = Vec::new();
+  let mut pos = 0;
+  while pos < design.len() {
+    let end = *choices.ends[pos]
+      .iter()
+      .min_by_key(|&&end| choices.fewest[end])
+      .unwrap();
+    arrangement.push(design[pos..end].to_string());
+    pos = end;
+  }
+  Some(arrangement)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::collections::HashSet;
+
+  const TOWELS: [&str; 8] = ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"];
+
+  fn towels() -> TowelTrie {
+    TowelTrie::from_towels(&TOWELS)
+  }
+
+  #[test]
+  fn sample() {
+    let towels = towels();
+    let designs = [
+      ("brwrr", 2),
+      ("bggr", 1),
+      ("gbbr", 4),
+      ("rrbgbr", 6),
+      ("ubwu", 0),
+      ("bwurrg", 1),
+      ("brgr", 2),
+      ("bbrgwb", 0),
+    ];
+    for (design, count) in designs {
+      let all: Vec<Vec<&str>> = Arrangements::new(design, &towels).collect();
+      assert_eq!(all.len(), count, "{}", design);
+      assert_eq!(all.iter().collect::<HashSet<_>>().len(), count);
+      for arrangement in &all {
+        assert_eq!(arrangement.concat(), design);
+        assert!(arrangement.iter().all(|t| TOWELS.contains(t)));
+      }
+
+      let fewest = all.iter().map(Vec::len).min();
+      let shortest = shortest(design, &towels);
+      assert_eq!(shortest.as_ref().map(Vec::len), fewest, "{}", design);
+      if let Some(shortest) = shortest {
+        assert_eq!(shortest.concat(), design);
+      }
+    }
+
+    assert_eq!(
+      Arrangements::new("brwrr", &towels).collect::<Vec<_>>(),
+      vec![vec!["b", "r", "wr", "r"], vec!["br", "wr", "r"]]
+    );
+    assert_eq!(shortest("gbbr", &towels).unwrap(), vec!["gb", "br"]);
+  }
+
+  #[test]
+  fn lazy() {
+    // Over 10^20 arrangements; only the first few are ever built.
+    let mut trie = TowelTrie::new();
+    trie.insert(&[Color::W]);
+    trie.insert(&[Color::W, Color::W]);
+    let design = "w".repeat(100);
+    let first: Vec<_> = Arrangements::new(&design, &trie).take(3).collect();
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[0].len(), 100);
+    assert_eq!(shortest(&design, &trie).unwrap().len(), 50);
+    assert_eq!(
+      Arrangements::new("", &trie).collect::<Vec<_>>(),
+      vec![Vec::<&str>::new()]
+    );
+  }
+}
@@ -0,0 +1,86 @@
+use crate::part1::is_design_possible;
+use crate::towel_trie::{Color, TowelTrie};
+
+/// The towels split into those needed to make every design that can be
+/// made, and those that can themselves be made from the others.
+pub struct Basis {
+  pub towels: Vec<String>,
+  pub redundant: Vec<String>,
+}
+
+impl Basis {
+  /// Finds the smallest subset of `towels` that can make exactly the same
+  /// designs. A towel can only be made from shorter ones (or a duplicate of
+  /// itself), so going from shortest to longest each towel only needs
+  /// checking against those already kept. The result is unique and comes
+  /// back in input order.
+  pub fn new(towels: &[String]) -> Basis {
+    let mut order: Vec<usize> = (0..towels.len()).collect();
+    order.sort_by_key(|&i| towels[i].len());
+
+    let mut trie = TowelTrie::new();
+    let mut keep = vec![false; towels.len()];
+    for i in order {
+      let colors = Color::from_str(&towels[i]).unwrap();
+      if !is_design_possible(&colors, &trie) {
+        trie.insert(&colors);
+        keep[i] = true;
+      }
+    }
+
+    let (kept, redundant): (Vec<_>, Vec<_>) =
+      towels.iter().zip(keep).partition(|&(_, keep)| keep);
+    Basis {
+      towels: kept.into_iter().map(|(t, _)| t.clone()).collect(),
+      redundant: redundant.into_iter().map(|(t, _)| t.clone()).collect(),
+    }
+  }
+
+  pub fn trie(&self) -> TowelTrie {
+    TowelTrie::from_towels(&self.towels)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn strings(towels: &[&str]) -> Vec<String> {
+    towels.iter().map(|&t| t.into()).collect()
+  }
+
+  #[test]
+  fn sample() {
+    let basis =
+      Basis::new(&strings(&["r", "wr", "b", "g", "bwu", "rb", "gb", "br"]));
+    assert_eq!(basis.towels, strings(&["r", "wr", "b", "g", "bwu"]));
+    assert_eq!(basis.redundant, strings(&["rb", "gb", "br"]));
+  }
+
+  #[test]
+  fn duplicates_and_chains() {
+    // "wu" is made from "w" and "u"; "wuwu" from "wu" twice, and so from
+    // the basis too. Only one "ub" is kept.
+    let basis = Basis::new(&strings(&["wuwu", "ub", "w", "wu", "u", "ub"]));
+    assert_eq!(basis.towels, strings(&["ub", "w", "u"]));
+    assert_eq!(basis.redundant, strings(&["wuwu", "wu", "ub"]));
+  }
+
+  #[test]
+  fn same_feasibility() {
+    for seed in 1..5 {
+      let input = crate::bench::generate(300, seed);
+      let all = TowelTrie::from_towels(&input.towels);
+      let basis = Basis::new(&input.towels).trie();
+      for design in &input.designs {
+        let colors = Color::from_str(design).unwrap();
+        assert_eq!(
+          is_design_possible(&colors, &basis),
+          is_design_possible(&colors, &all),
+          "{}",
+          design
+        );
+      }
+    }
+  }
+}
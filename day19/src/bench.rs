@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use crate::input::Input;
+use crate::part2;
+
+const COLORS: [char; 5] = ['w', 'u', 'b', 'r', 'g'];
+const TOWELS: usize = 400;
+const DESIGN_LEN: usize = 60;
+
+// Generates an input shaped like the puzzle's, with `designs` designs, and
+// times counting its arrangements with the automaton and with the
+// recursion.
+pub fn run(designs: usize) {
+  let input = generate(designs, 1);
+
+  let start = Instant::now();
+  let total = part2::eval(&input).to_string();
+  report("automaton", designs, start.elapsed(), &total);
+
+  let start = Instant::now();
+  let recursive = part2::count_recursive(&input).to_string();
+  report("recursive", designs, start.elapsed(), &recursive);
+
+  assert_eq!(total, recursive);
+}
+
+// Towels are 2 to 8 colors long, which keeps the counts for designs of
+// this length well within an i64. Designs are mostly made of towels, with
+// one in four having a random color thrown in.
+pub fn generate(designs: usize, seed: u64) -> Input {
+  let mut rng = seed;
+  let mut next = |n: usize| {
+    rng = rng
+      .wrapping_mul(6364136223846793005)
+      .wrapping_add(1442695040888963407);
+    (rng >> 33) as usize % n
+  };
+
+  let towels: Vec<String> = (0..TOWELS)
+    .map(|_| (0..2 + next(7)).map(|_| COLORS[next(5)]).collect())
+    .collect();
+  let designs = (0..designs)
+    .map(|_| {
+      let mut design = String::new();
+      while design.len() < DESIGN_LEN {
+        design += &towels[next(TOWELS)];
+        if next(4) == 0 {
+          design.push(COLORS[next(5)]);
+        }
+      }
+      design.truncate(DESIGN_LEN);
+      design
+    })
+    .collect();
+
+  Input { towels, designs }
+}
+
+fn report(name: &str, designs: usize, elapsed: Duration, total: &str) {
+  println!(
+    "{}: {} designs in {:.3?} ({:.0} designs/s, {} arrangements)",
+    name,
+    designs,
+    elapsed,
+    designs as f64 / elapsed.as_secs_f64(),
+    total
+  );
+}
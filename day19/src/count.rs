@@ -0,0 +1,181 @@
+use std::fmt;
+
+/// A number of arrangements. Addition is the only operation counting needs,
+/// and it reports overflow rather than wrapping.
+pub trait Count: Clone {
+  /// Zero, in the same setting as `self` (e.g. the same modulus).
+  fn zero_like(&self) -> Self;
+  fn checked_add(&self, rhs: &Self) -> Option<Self>;
+}
+
+macro_rules! impl_count {
+  ($($t:ty),*) => {$(
+    impl Count for $t {
+      fn zero_like(&self) -> Self {
+        0
+      }
+
+      fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        <$t>::checked_add(*self, *rhs)
+      }
+    }
+  )*};
+}
+
+impl_count!(u64, u128);
+
+/// A count modulo `modulus`, which never overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modular {
+  pub value: u64,
+  pub modulus: u64,
+}
+
+impl Modular {
+  pub fn one(modulus: u64) -> Modular {
+    assert!(modulus > 0);
+    Modular {
+      value: 1 % modulus,
+      modulus,
+    }
+  }
+}
+
+impl Count for Modular {
+  fn zero_like(&self) -> Self {
+    Modular {
+      value: 0,
+      modulus: self.modulus,
+    }
+  }
+
+  fn checked_add(&self, rhs: &Self) -> Option<Self> {
+    let sum = (self.value as u128 + rhs.value as u128) % self.modulus as u128;
+    Some(Modular {
+      value: sum as u64,
+      modulus: self.modulus,
+    })
+  }
+}
+
+/// An arbitrary precision count, stored as base 2^32 digits, least
+/// significant first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint(Vec<u32>);
+
+impl From<u64> for BigUint {
+  fn from(n: u64) -> Self {
+    let mut digits = vec![n as u32, (n >> 32) as u32];
+    while digits.last() == Some(&0) {
+      digits.pop();
+    }
+    BigUint(digits)
+  }
+}
+
+impl Count for BigUint {
+  fn zero_like(&self) -> Self {
+    BigUint(Vec::new())
+  }
+
+  fn checked_add(&self, rhs: &Self) -> Option<Self> {
+    let (long, short) = if self.0.len() >= rhs.0.len() {
+      (&self.0, &rhs.0)
+    } else {
+      (&rhs.0, &self.0)
+    };
+    let mut digits = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &d) in long.iter().enumerate() {
+      let sum = d as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+      digits.push(sum as u32);
+      carry = sum >> 32;
+    }
+    if carry > 0 {
+      digits.push(carry as u32);
+    }
+    Some(BigUint(digits))
+  }
+}
+
+impl fmt::Display for BigUint {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // Peel off base 10^9 chunks by long division, least significant first.
+    const CHUNK: u64 = 1_000_000_000;
+    let mut digits = self.0.clone();
+    let mut chunks = Vec::new();
+    while !digits.is_empty() {
+      let mut rem = 0u64;
+      for d in digits.iter_mut().rev() {
+        let cur = (rem << 32) | *d as u64;
+        *d = (cur / CHUNK) as u32;
+        rem = cur % CHUNK;
+      }
+      chunks.push(rem);
+      while digits.last() == Some(&0) {
+        digits.pop();
+      }
+    }
+
+    match chunks.split_last() {
+      None => write!(f, "0"),
+      Some((first, rest)) => {
+        write!(f, "{}", first)?;
+        for chunk in rest.iter().rev() {
+          write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// A total in the narrowest type it fits in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Total {
+  U64(u64),
+  U128(u128),
+  Big(BigUint),
+}
+
+impl fmt::Display for Total {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Total::U64(n) => write!(f, "{}", n),
+      Total::U128(n) => write!(f, "{}", n),
+      Total::Big(n) => write!(f, "{}", n),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn big_uint() {
+    let mut n = BigUint::from(u64::MAX);
+    assert_eq!(n.to_string(), "18446744073709551615");
+    n = n.checked_add(&BigUint::from(1)).unwrap();
+    assert_eq!(n.to_string(), "18446744073709551616");
+    for _ in 0..6 {
+      n = n.checked_add(&n).unwrap();
+    }
+    assert_eq!(n.to_string(), (1u128 << 70).to_string());
+    assert_eq!(BigUint::from(0).to_string(), "0");
+    assert_eq!(BigUint::from(1_000_000_000).to_string(), "1000000000");
+  }
+
+  #[test]
+  fn checked() {
+    assert_eq!(Count::checked_add(&u64::MAX, &1), None);
+    assert_eq!(Count::checked_add(&(u64::MAX as u128), &1), Some(1 << 64));
+    let m = Modular::one(7);
+    let six = Modular {
+      value: 6,
+      modulus: 7,
+    };
+    assert_eq!(six.checked_add(&m).unwrap().value, 0);
+    assert_eq!(Modular::one(1).value, 0);
+  }
+}
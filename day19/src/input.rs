@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::towel_trie::Color;
+
+/// Where and why the input couldn't be parsed. Lines and columns are
+/// 1-based; columns count characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+  Empty,
+  InvalidColor { line: usize, col: usize, c: char },
+  EmptyTowel { line: usize, col: usize },
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::Empty => write!(f, "no towels"),
+      ParseError::InvalidColor { line, col, c } => {
+        write!(f, "{}:{}: invalid color {:?}", line, col, c)
+      }
+      ParseError::EmptyTowel { line, col } => {
+        write!(f, "{}:{}: empty towel", line, col)
+      }
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+pub struct Input {
+  pub towels: Vec<String>,
+  pub designs: Vec<String>,
+}
+
+impl Input {
+  /// Parses a line of comma separated towels, then one design per line.
+  /// Blank lines are skipped wherever they are, and surrounding whitespace
+  /// (including the '\r' of Windows line endings) is ignored. Every towel
+  /// and design is checked to be made only of known colors, so later
+  /// conversions with `Color::from_str` can't fail.
+  pub fn parse(s: &str) -> Result<Input, ParseError> {
+    let mut lines = s
+      .lines()
+      .enumerate()
+      .map(|(i, l)| (i + 1, l))
+      .filter(|(_, l)| !l.trim().is_empty());
+
+    let (line, towels_line) = lines.next().ok_or(ParseError::Empty)?;
+    let mut towels = Vec::new();
+    let mut offset = 0;
+    for towel in towels_line.split(',') {
+      let col = column(towels_line, offset, towel);
+      let trimmed = towel.trim();
+      if trimmed.is_empty() {
+        return Err(ParseError::EmptyTowel { line, col });
+      }
+      check_colors(trimmed, line, col)?;
+      towels.push(trimmed.to_string());
+      offset += towel.len() + 1;
+    }
+
+    let designs = lines
+      .map(|(line, l)| {
+        let design = l.trim();
+        check_colors(design, line, column(l, 0, l))?;
+        Ok(design.to_string())
+      })
+      .collect::<Result<Vec<String>, ParseError>>()?;
+
+    Ok(Input { towels, designs })
+  }
+}
+
+// Column of the first non-whitespace character of `field`, which starts at
+// byte `offset` of `line`.
+fn column(line: &str, offset: usize, field: &str) -> usize {
+  let leading = field.len() - field.trim_start().len();
+  line[..offset + leading].chars().count() + 1
+}
+
+// Checks that `s`, starting at column `col`, is made only of colors.
+fn check_colors(s: &str, line: usize, col: usize) -> Result<(), ParseError> {
+  match s
+    .chars()
+    .enumerate()
+    .find(|&(_, c)| Color::from_char(c).is_none())
+  {
+    Some((i, c)) => Err(ParseError::InvalidColor {
+      line,
+      col: col + i,
+      c,
+    }),
+    None => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parse() {
+    let input =
+      Input::parse("\r\nr, wr ,b\r\n\r\n\r\nbrwrr\r\n\r\n  bggr\r\n").unwrap();
+    assert_eq!(input.towels, vec!["r", "wr", "b"]);
+    assert_eq!(input.designs, vec!["brwrr", "bggr"]);
+
+    // No blank line between towels and designs.
+    let input = Input::parse("r, b\nrb\n").unwrap();
+    assert_eq!(input.designs, vec!["rb"]);
+  }
+
+  #[test]
+  fn parse_errors() {
+    let err = |s| Input::parse(s).err().unwrap();
+    assert_eq!(err(""), ParseError::Empty);
+    assert_eq!(err("\n \n"), ParseError::Empty);
+    assert_eq!(
+      err("r, wx, b\n\nrb\n"),
+      ParseError::InvalidColor {
+        line: 1,
+        col: 5,
+        c: 'x'
+      }
+    );
+    assert_eq!(
+      err("r, b\n\nrb\n  rbR\n"),
+      ParseError::InvalidColor {
+        line: 4,
+        col: 5,
+        c: 'R'
+      }
+    );
+    assert_eq!(err("r,, b\n"), ParseError::EmptyTowel { line: 1, col: 3 });
+    assert_eq!(err("r, b\n\nr b\n").to_string(), "3:2: invalid color ' '");
+    // Columns count characters, not bytes.
+    assert_eq!(
+      err("r, é\n"),
+      ParseError::InvalidColor {
+        line: 1,
+        col: 4,
+        c: 'é'
+      }
+    );
+  }
+}
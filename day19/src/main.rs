@@ -1,9 +1,95 @@
-mod towel_trie;
+mod aho_corasick;
+mod arrangement;
+mod basis;
+mod bench;
+mod count;
+mod input;
 mod part1;
 mod part2;
+mod towel_trie;
+mod trie;
+
+use arrangement::Arrangements;
+use basis::Basis;
+use input::Input;
+use towel_trie::TowelTrie;
+
+const USAGE: &str = "usage: day19 [filename] [--arrangements [limit]] \
+  [--shortest] [--mod <m>] [--basis]\n       \
+  day19 --bench <designs>";
 
 fn main() {
-  let input = std::fs::read_to_string("input.txt").unwrap();
+  let mut filename = "input.txt".to_string();
+  let mut list: Option<usize> = None;
+  let mut shortest = false;
+  let mut modulus: Option<u64> = None;
+  let mut show_basis = false;
+
+  let mut args = std::env::args().skip(1).peekable();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--arrangements" => {
+        let limit = args.next_if(|a| !a.starts_with("--"));
+        list = Some(limit.map_or(usize::MAX, |l| l.parse().unwrap()));
+      }
+      "--shortest" => shortest = true,
+      "--basis" => show_basis = true,
+      "--mod" => modulus = Some(args.next().expect(USAGE).parse().unwrap()),
+      "--bench" => {
+        let designs = args.next().expect(USAGE).parse().unwrap();
+        bench::run(designs);
+        return;
+      }
+      _ if !arg.starts_with("--") => filename = arg,
+      _ => panic!("unexpected argument {:?}\n{}", arg, USAGE),
+    }
+  }
+
+  let input = std::fs::read_to_string(&filename).unwrap();
+  let input = match Input::parse(&input) {
+    Ok(input) => input,
+    Err(err) => {
+      eprintln!("{}:{}", filename, err);
+      std::process::exit(1);
+    }
+  };
   println!("part1={}", part1::eval(&input));
   println!("part2={}", part2::eval(&input));
+  if let Some(modulus) = modulus {
+    let count = part2::count_mod(&input, modulus);
+    println!("part2 mod {}={}", modulus, count);
+  }
+
+  if show_basis {
+    let basis = Basis::new(&input.towels);
+    let trie = basis.trie();
+    println!(
+      "basis: {} of {} towels",
+      basis.towels.len(),
+      input.towels.len()
+    );
+    for towel in &basis.redundant {
+      let parts = Arrangements::new(towel, &trie).next().unwrap();
+      println!("  {} = {}", towel, parts.join(","));
+    }
+  }
+
+  if list.is_none() && !shortest {
+    return;
+  }
+  let towels = TowelTrie::from_towels(&input.towels);
+  for design in &input.designs {
+    if let Some(limit) = list {
+      println!("{}:", design);
+      for arrangement in Arrangements::new(design, &towels).take(limit) {
+        println!("  {}", arrangement.join(","));
+      }
+    }
+    if shortest {
+      match arrangement::shortest(design, &towels) {
+        Some(towels) => println!("{}: shortest {}", design, towels.join(",")),
+        None => println!("{}: impossible", design),
+      }
+    }
+  }
 }
@@ -1,68 +1,21 @@
+use crate::input::Input;
 use crate::towel_trie::{Color, TowelTrie};
 
-pub struct Input {
-  pub towels: Vec<String>,
-  pub designs: Vec<String>,
-}
-
-impl Input {
-  pub fn parse(s: &str) -> Input {
-    let mut lines = s.lines();
-
-    let towels_line = lines.next().unwrap();
-    let towels = towels_line
-      .split(",")
-      .map(str::trim)
-      .map(String::from)
-      .collect();
-
-    // Skip empty line.
-    lines.next().unwrap();
-
-    let designs = lines
-      .map(str::trim)
-      .filter(|l| !l.is_empty())
-      .map(String::from)
-      .collect::<Vec<String>>();
-
-    Input { towels, designs }
-  }
-}
-
-pub fn eval(s: &str) -> usize {
-  let input = Input::parse(s);
-
-  let mut trie = TowelTrie::new();
-  for towel in &input.towels {
-    let colors = Color::from_str(towel).unwrap();
-    trie.insert(&colors);
-  }
+pub fn eval(input: &Input) -> usize {
+  let trie = TowelTrie::from_towels(&input.towels);
 
   input
     .designs
     .iter()
-    .filter(|&d| is_design_possible(d, &trie))
+    .filter(|&d| is_design_possible(&Color::from_str(d).unwrap(), &trie))
     .count()
 }
 
-fn is_design_possible(design: &str, towels: &TowelTrie) -> bool {
-  let design_colors = Color::from_str(design).unwrap();
-
-  let mut node = 0;
-  for i in 0..design_colors.len() {
-    match towels.advance(node, design_colors[i]) {
-      Some(next) => {
-        node = next;
-        if towels.is_word(node) && is_design_possible(&design[i + 1..], towels)
-        {
-          return true;
-        }
-      }
-      None => return false,
-    }
-  }
-
-  true
+pub fn is_design_possible(design: &[Color], towels: &TowelTrie) -> bool {
+  design.is_empty()
+    || towels
+      .prefixes(design)
+      .any(|len| is_design_possible(&design[len..], towels))
 }
 
 #[cfg(test)]
@@ -83,6 +36,13 @@ brgr
 bbrgwb
 ";
 
-    assert_eq!(eval(input), 6);
+    assert_eq!(eval(&Input::parse(input).unwrap()), 6);
+  }
+
+  #[test]
+  fn design_ends_inside_towel() {
+    // "bw" runs out halfway through "bwu", which isn't a whole towel.
+    let input = Input::parse("b, bwu\n\nbw\nbwub\n").unwrap();
+    assert_eq!(eval(&input), 1);
   }
 }
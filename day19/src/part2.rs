@@ -1,43 +1,62 @@
+use crate::aho_corasick::Automaton;
+use crate::count::{BigUint, Count, Modular, Total};
+use crate::input::Input;
 use crate::towel_trie::{Color, TowelTrie};
 use std::collections::HashMap;
 
-pub struct Input {
-  pub towels: Vec<String>,
-  pub designs: Vec<String>,
+/// Counts the arrangements of every design in one pass over each, finding
+/// the towels that end at each position with an Aho-Corasick automaton.
+/// Counts in a u64, falling back to a u128 and then to arbitrary precision
+/// if that overflows.
+pub fn eval(input: &Input) -> Total {
+  let (automaton, designs) = prepare(input);
+  if let Some(total) = sum(&automaton, &designs, &1u64) {
+    return Total::U64(total);
+  }
+  if let Some(total) = sum(&automaton, &designs, &1u128) {
+    return Total::U128(total);
+  }
+  Total::Big(sum(&automaton, &designs, &BigUint::from(1)).unwrap())
 }
 
-impl Input {
-  pub fn parse(s: &str) -> Input {
-    let mut lines = s.lines();
-
-    let towels_line = lines.next().unwrap();
-    let towels = towels_line
-      .split(",")
-      .map(str::trim)
-      .map(String::from)
-      .collect();
-
-    // Skip empty line.
-    lines.next().unwrap();
-
-    let designs = lines
-      .map(str::trim)
-      .filter(|l| !l.is_empty())
-      .map(String::from)
-      .collect::<Vec<String>>();
+/// Like `eval`, but modulo `modulus`.
+pub fn count_mod(input: &Input, modulus: u64) -> u64 {
+  let (automaton, designs) = prepare(input);
+  sum(&automaton, &designs, &Modular::one(modulus))
+    .unwrap()
+    .value
+}
 
-    Input { towels, designs }
-  }
+fn prepare(input: &Input) -> (Automaton, Vec<Vec<Color>>) {
+  let towels: Vec<Vec<Color>> = input
+    .towels
+    .iter()
+    .map(|t| Color::from_str(t).unwrap())
+    .collect();
+  let automaton = Automaton::new(towels.iter().map(Vec::as_slice));
+  let designs = input
+    .designs
+    .iter()
+    .map(|d| Color::from_str(d).unwrap())
+    .collect();
+  (automaton, designs)
 }
 
-pub fn eval(s: &str) -> i64 {
-  let input = Input::parse(s);
+fn sum<C: Count>(
+  automaton: &Automaton,
+  designs: &[Vec<Color>],
+  one: &C,
+) -> Option<C> {
+  designs.iter().try_fold(one.zero_like(), |total, design| {
+    total.checked_add(&automaton.count(design, one)?)
+  })
+}
 
-  let mut trie = TowelTrie::new();
-  for towel in &input.towels {
-    let colors = Color::from_str(towel).unwrap();
-    trie.insert(&colors);
-  }
+/// Counts the arrangements by recursing on each suffix, memoised by the
+/// suffix itself. Slower than `eval`; kept to check and benchmark it
+/// against.
+pub fn count_recursive(input: &Input) -> i64 {
+  let trie = TowelTrie::from_towels(&input.towels);
 
   let mut memo: HashMap<String, i64> = HashMap::new();
 
@@ -53,7 +72,7 @@ fn design_combinations(
   towels: &TowelTrie,
   memo: &mut HashMap<String, i64>,
 ) -> i64 {
-  if design.len() == 0 {
+  if design.is_empty() {
     return 1;
   }
 
@@ -64,17 +83,12 @@ fn design_combinations(
   let design_colors = Color::from_str(design).unwrap();
 
   let mut combs = 0;
-  let mut node = 0;
-  for i in 0..design_colors.len() {
-    match towels.advance(node, design_colors[i]) {
-      Some(next) => {
-        node = next;
-        if towels.is_word(node) {
-          combs += design_combinations(&design[i + 1..], towels, memo);
-        }
-      }
-      None => break,
-    }
+  for len in towels.prefixes(&design_colors) {
+    combs = i64::checked_add(
+      combs,
+      design_combinations(&design[len..], towels, memo),
+    )
+    .expect("arrangement count overflowed");
   }
 
   memo.insert(design.into(), combs);
@@ -99,6 +113,42 @@ brgr
 bbrgwb
 ";
 
-    assert_eq!(eval(input), 16);
+    let input = Input::parse(input).unwrap();
+    assert_eq!(eval(&input), Total::U64(16));
+    assert_eq!(count_recursive(&input), 16);
+  }
+
+  #[test]
+  fn automaton_matches_recursion() {
+    for seed in 1..5 {
+      let input = crate::bench::generate(200, seed);
+      let recursive = count_recursive(&input) as u64;
+      assert_eq!(eval(&input), Total::U64(recursive));
+    }
+  }
+
+  #[test]
+  fn wide_counts() {
+    // Fibonacci many arrangements: F(101) needs a u128, F(201) more.
+    let input = Input {
+      towels: vec!["w".into(), "ww".into()],
+      designs: vec!["w".repeat(100)],
+    };
+    let f101 = 573147844013817084101u128;
+    assert_eq!(eval(&input), Total::U128(f101));
+    assert_eq!(
+      count_mod(&input, 1_000_000_007),
+      (f101 % 1_000_000_007) as u64
+    );
+
+    let input = Input {
+      designs: vec!["w".repeat(200), "w".into()],
+      ..input
+    };
+    assert_eq!(
+      eval(&input).to_string(),
+      "453973694165307953197296969697410619233827"
+    );
+    assert_eq!(count_mod(&input, 1_000_000_007), 529309712);
   }
 }
@@ -1,3 +1,5 @@
+use crate::trie::{Dense, DenseEdges, Trie};
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Color {
   W = 0,
@@ -8,8 +10,6 @@ pub enum Color {
 }
 
 impl Color {
-  const COUNT: usize = 5;
-
   pub fn from_char(ch: char) -> Option<Color> {
     match ch {
       'w' => Some(Color::W),
@@ -23,73 +23,30 @@ impl Color {
 
   pub fn from_str(s: &str) -> Option<Vec<Color>> {
     s.chars()
-      .map(|c| Color::from_char(c))
+      .map(Color::from_char)
       .collect::<Option<Vec<Color>>>()
   }
 }
 
-#[derive(Default)]
-struct Node {
-  is_word: bool,
-  next: [Option<u16>; Color::COUNT],
-}
-
-pub struct TowelTrie {
-  nodes: Vec<Node>,
-}
+impl Dense for Color {
+  const COUNT: usize = 5;
 
-impl TowelTrie {
-  pub fn new() -> TowelTrie {
-    TowelTrie {
-      nodes: vec![Node {
-        ..Default::default()
-      }],
-    }
+  fn index(self) -> usize {
+    self as usize
   }
+}
 
-  pub fn insert(&mut self, colors: &[Color]) -> bool {
-    assert!(!colors.is_empty());
-
-    let mut colors = colors;
-    let mut node = 0;
-    let mut inserted = false;
-
-    while !colors.is_empty() {
-      let color = colors[0];
-      colors = &colors[1..];
-
-      if let Some(next) = self.nodes[node].next[color as usize] {
-        node = next as usize;
-      } else {
-        let new_node = self.new_node();
-        self.nodes[node].next[color as usize] = Some(new_node as u16);
-        inserted = true;
-        node = new_node;
-      }
-    }
+pub type TowelTrie = Trie<Color, DenseEdges<{ Color::COUNT }>>;
 
-    if !self.nodes[node].is_word {
-      self.nodes[node].is_word = true;
-      inserted = true;
+impl TowelTrie {
+  /// A trie holding every towel. Panics on a towel with a character that
+  /// isn't a color, which `Input::parse` already rejects.
+  pub fn from_towels<T: AsRef<str>>(towels: &[T]) -> TowelTrie {
+    let mut trie = TowelTrie::new();
+    for towel in towels {
+      trie.insert(&Color::from_str(towel.as_ref()).unwrap());
     }
-
-    inserted
-  }
-
-  pub fn advance(&self, node: usize, c: Color) -> Option<usize> {
-    self.nodes[node].next[c as usize].and_then(|n| Some(n as usize))
-  }
-
-  pub fn is_word(&self, node: usize) -> bool {
-    self.nodes[node].is_word
-  }
-
-  fn new_node<'a>(&'a mut self) -> usize {
-    let index = self.nodes.len();
-    self.nodes.push(Node {
-      ..Default::default()
-    });
-    index
+    trie
   }
 }
 
@@ -109,7 +66,7 @@ mod test {
     trie.insert(&[Color::G, Color::B]);
     trie.insert(&[Color::B, Color::R]);
 
-    let mut node = trie.advance(0, Color::B).unwrap();
+    let mut node = trie.advance(TowelTrie::ROOT, Color::B).unwrap();
     assert!(trie.is_word(node));
     node = trie.advance(node, Color::W).unwrap();
     assert!(!trie.is_word(node));
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A symbol from a small alphabet, usable as an array index.
+pub trait Dense: Copy {
+  const COUNT: usize;
+
+  fn index(self) -> usize;
+}
+
+/// The outgoing edges of a trie node. Node ids are stored as `u32` to keep
+/// nodes small.
+pub trait Edges<S>: Default {
+  fn get(&self, sym: S) -> Option<u32>;
+  fn set(&mut self, sym: S, node: u32);
+}
+
+/// Edges stored in an array indexed by symbol, for an alphabet of `N`
+/// symbols. `N` must be the alphabet's `Dense::COUNT`, which is checked at
+/// compile time.
+pub struct DenseEdges<const N: usize>([Option<u32>; N]);
+
+impl<const N: usize> Default for DenseEdges<N> {
+  fn default() -> Self {
+    DenseEdges([None; N])
+  }
+}
+
+impl<const N: usize> DenseEdges<N> {
+  fn slot<S: Dense>(sym: S) -> usize {
+    const { assert!(N == S::COUNT, "DenseEdges<N> needs N == S::COUNT") };
+    sym.index()
+  }
+}
+
+impl<S: Dense, const N: usize> Edges<S> for DenseEdges<N> {
+  fn get(&self, sym: S) -> Option<u32> {
+    self.0[Self::slot(sym)]
+  }
+
+  fn set(&mut self, sym: S, node: u32) {
+    self.0[Self::slot(sym)] = Some(node);
+  }
+}
+
+/// Edges stored in a hash map, for alphabets too large to index directly.
+pub struct SparseEdges<S>(HashMap<S, u32>);
+
+impl<S> Default for SparseEdges<S> {
+  fn default() -> Self {
+    SparseEdges(HashMap::new())
+  }
+}
+
+impl<S: Copy + Eq + Hash> Edges<S> for SparseEdges<S> {
+  fn get(&self, sym: S) -> Option<u32> {
+    self.0.get(&sym).copied()
+  }
+
+  fn set(&mut self, sym: S, node: u32) {
+    self.0.insert(sym, node);
+  }
+}
+
+struct Node<E> {
+  is_word: bool,
+  next: E,
+}
+
+/// A set of words over the symbols `S`, walked one symbol at a time from
+/// `Trie::ROOT`.
+pub struct Trie<S, E = SparseEdges<S>> {
+  nodes: Vec<Node<E>>,
+  symbols: PhantomData<S>,
+}
+
+impl<S: Copy, E: Edges<S>> Trie<S, E> {
+  pub const ROOT: usize = 0;
+
+  pub fn new() -> Self {
+    Trie {
+      nodes: vec![Node {
+        is_word: false,
+        next: E::default(),
+      }],
+      symbols: PhantomData,
+    }
+  }
+
+  /// Adds `word`, returning false if it was already there.
+  pub fn insert(&mut self, word: &[S]) -> bool {
+    assert!(!word.is_empty());
+
+    let mut node = Self::ROOT;
+    for &sym in word {
+      node = match self.advance(node, sym) {
+        Some(next) => next,
+        None => {
+          let new_node = self.new_node();
+          self.nodes[node].next.set(sym, new_node);
+          new_node as usize
+        }
+      };
+    }
+
+    !std::mem::replace(&mut self.nodes[node].is_word, true)
+  }
+
+  pub fn advance(&self, node: usize, sym: S) -> Option<usize> {
+    self.nodes[node].next.get(sym).map(|n| n as usize)
+  }
+
+  pub fn is_word(&self, node: usize) -> bool {
+    self.nodes[node].is_word
+  }
+
+  /// Lengths of the prefixes of `word` that are in the trie, shortest
+  /// first. The last one is the longest match.
+  pub fn prefixes<'a>(&'a self, word: &'a [S]) -> Prefixes<'a, S, E> {
+    Prefixes {
+      trie: self,
+      word,
+      node: Some(Self::ROOT),
+      len: 0,
+    }
+  }
+
+  fn new_node(&mut self) -> u32 {
+    let index = u32::try_from(self.nodes.len()).expect("too many trie nodes");
+    self.nodes.push(Node {
+      is_word: false,
+      next: E::default(),
+    });
+    index
+  }
+}
+
+pub struct Prefixes<'a, S, E> {
+  trie: &'a Trie<S, E>,
+  word: &'a [S],
+  // None once the walk has left the trie.
+  node: Option<usize>,
+  len: usize,
+}
+
+impl<S: Copy, E: Edges<S>> Iterator for Prefixes<'_, S, E> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    while let Some(node) = self.node {
+      self.node = self
+        .word
+        .get(self.len)
+        .and_then(|&sym| self.trie.advance(node, sym));
+      self.len += 1;
+      if let Some(next) = self.node {
+        if self.trie.is_word(next) {
+          return Some(self.len);
+        }
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn sparse() {
+    let mut trie: Trie<char> = Trie::new();
+    for word in ["a", "ab", "abcd", "b"] {
+      assert!(trie.insert(&word.chars().collect::<Vec<_>>()));
+    }
+    assert!(!trie.insert(&['a', 'b']));
+
+    let word: Vec<char> = "abcde".chars().collect();
+    assert_eq!(trie.prefixes(&word).collect::<Vec<_>>(), vec![1, 2, 4]);
+    assert_eq!(trie.prefixes(&word).last(), Some(4));
+    assert_eq!(trie.prefixes(&['a', 'c']).last(), Some(1));
+    assert_eq!(trie.prefixes(&['c']).last(), None);
+    assert_eq!(trie.prefixes(&[]).count(), 0);
+  }
+
+  #[test]
+  fn many_nodes() {
+    // More nodes than fit in a u16.
+    let mut trie: Trie<u8> = Trie::new();
+    for i in 0..20000u32 {
+      trie.insert(format!("{:08}", i * 48271).as_bytes());
+    }
+    assert!(trie.nodes.len() > u16::MAX as usize);
+    assert_eq!(trie.prefixes(b"482710001").last(), Some(8));
+    assert_eq!(trie.prefixes(b"48271001").last(), None);
+  }
+}
@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::input::{Input, V};
+use crate::track::TrackIssue;
+
+/// Number of steps from a source position to every track position, or None
+/// for walls and unreachable positions.
+pub struct DistMap {
+  dists: Vec<Vec<Option<i32>>>,
+}
+
+impl DistMap {
+  pub fn new(input: &Input, from: V) -> DistMap {
+    let mut dists =
+      vec![vec![None; input.width as usize]; input.height as usize];
+    let mut queue = VecDeque::new();
+    dists[from.y() as usize][from.x() as usize] = Some(0);
+    queue.push_back((from, 0));
+
+    while let Some((pos, dist)) = queue.pop_front() {
+      for d in [V(0, -1), V(0, 1), V(-1, 0), V(1, 0)] {
+        let next = pos + d;
+        if !input.is_track(next) {
+          continue;
+        }
+        let entry = &mut dists[next.y() as usize][next.x() as usize];
+        if entry.is_none() {
+          *entry = Some(dist + 1);
+          queue.push_back((next, dist + 1));
+        }
+      }
+    }
+
+    DistMap { dists }
+  }
+
+  /// Distances along an ordered list of positions: the first is 0 steps away,
+  /// the next 1, and so on.
+  pub fn along(input: &Input, positions: impl Iterator<Item = V>) -> DistMap {
+    let mut dists =
+      vec![vec![None; input.width as usize]; input.height as usize];
+    for (i, pos) in positions.enumerate() {
+      dists[pos.y() as usize][pos.x() as usize] = Some(i as i32);
+    }
+    DistMap { dists }
+  }
+
+  pub fn get(&self, pos: V) -> Option<i32> {
+    if pos.x() < 0 || pos.y() < 0 {
+      return None;
+    }
+    *self.dists.get(pos.y() as usize)?.get(pos.x() as usize)?
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cheat {
+  /// Last track position before going through walls.
+  pub start: V,
+  /// First track position after the cheat ends.
+  pub end: V,
+  /// Picoseconds saved over the best time without cheating.
+  pub saving: i32,
+}
+
+/// The race without cheating: the best time and how far every track position
+/// is from the start and from the end. There's no race if E can't be reached
+/// from S.
+pub struct Race<'a> {
+  input: &'a Input,
+  pub best: i32,
+  from_start: DistMap,
+  to_end: DistMap,
+}
+
+impl<'a> Race<'a> {
+  /// Sets up the race from the result of `track::walk`, so the caller can
+  /// report the track's issues without walking it again.
+  pub fn new(
+    input: &'a Input,
+    walk: &Result<Vec<V>, Vec<TrackIssue>>,
+  ) -> Option<Race<'a>> {
+    match walk {
+      // A single corridor: distances are just positions along it.
+      Ok(track) => Some(Race {
+        input,
+        best: track.len() as i32 - 1,
+        from_start: DistMap::along(input, track.iter().copied()),
+        to_end: DistMap::along(input, track.iter().rev().copied()),
+      }),
+      Err(_) => Race::search(input),
+    }
+  }
+
+  fn search(input: &'a Input) -> Option<Race<'a>> {
+    let from_start = DistMap::new(input, input.start);
+    Some(Race {
+      input,
+      best: from_start.get(input.end)?,
+      from_start,
+      to_end: DistMap::new(input, input.end),
+    })
+  }
+
+  /// The cheat from `start` to `end`, going through walls for their
+  /// Manhattan distance, or None if either isn't on the track or they're
+  /// less than 2 apart. The saving can be zero or negative.
+  pub fn cheat(&self, start: V, end: V) -> Option<Cheat> {
+    let delta = end - start;
+    let len = delta.x().abs() + delta.y().abs();
+    if len < 2 {
+      return None;
+    }
+    let ds = self.from_start.get(start)?;
+    let de = self.to_end.get(end)?;
+    Some(Cheat {
+      start,
+      end,
+      saving: self.best - (ds + len + de),
+    })
+  }
+
+  /// Calls `f` for every cheat that saves at least `min_saving` picoseconds,
+  /// where a cheat lets you pass through walls for up to `max_cheat`
+  /// picoseconds. A cheat is identified by its start and end positions, both
+  /// on the track, and takes at least 2 picoseconds: anything shorter is just
+  /// racing.
+  fn for_each_cheat<F>(&self, max_cheat: i32, min_saving: i32, mut f: F)
+  where
+    F: FnMut(Cheat),
+  {
+    for y in 0..self.input.height {
+      for x in 0..self.input.width {
+        let start = V(x, y);
+        if self.from_start.get(start).is_none() {
+          continue;
+        }
+        for dy in -max_cheat..=max_cheat {
+          let rem = max_cheat - dy.abs();
+          for dx in -rem..=rem {
+            match self.cheat(start, start + V(dx, dy)) {
+              Some(cheat) if cheat.saving >= min_saving => f(cheat),
+              _ => {}
+            }
+          }
+        }
+      }
+    }
+  }
+
+  pub fn count_cheats(&self, max_cheat: i32, min_saving: i32) -> usize {
+    let mut count = 0;
+    self.for_each_cheat(max_cheat, min_saving, |_| count += 1);
+    count
+  }
+
+  /// Same as `count_cheats`, but returns the cheats themselves, ordered by
+  /// start position.
+  pub fn find_cheats(&self, max_cheat: i32, min_saving: i32) -> Vec<Cheat> {
+    let mut cheats = Vec::new();
+    self.for_each_cheat(max_cheat, min_saving, |c| cheats.push(c));
+    cheats
+  }
+}
+
+/// Number of cheats for each saving.
+pub fn histogram(cheats: &[Cheat]) -> BTreeMap<i32, usize> {
+  let mut hist = BTreeMap::new();
+  for cheat in cheats {
+    *hist.entry(cheat.saving).or_default() += 1;
+  }
+  hist
+}
+
+/// Draws the map with the cheat's steps numbered like the puzzle examples,
+/// going vertically first and then horizontally. Steps after 9 continue with
+/// letters. S and E are drawn over any step that lands on them.
+pub fn render(input: &Input, cheat: &Cheat) -> String {
+  let mut grid: Vec<Vec<char>> = input
+    .rows
+    .iter()
+    .map(|row| row.iter().map(|&t| if t { '.' } else { '#' }).collect())
+    .collect();
+
+  let delta = cheat.end - cheat.start;
+  let steps = std::iter::repeat_n(
+    V(0, delta.y().signum()),
+    delta.y().unsigned_abs() as usize,
+  )
+  .chain(std::iter::repeat_n(
+    V(delta.x().signum(), 0),
+    delta.x().unsigned_abs() as usize,
+  ));
+  let mut pos = cheat.start;
+  for (n, step) in steps.enumerate() {
+    pos = pos + step;
+    grid[pos.y() as usize][pos.x() as usize] =
+      char::from_digit(n as u32 + 1, 36).unwrap_or('*');
+  }
+  grid[input.start.y() as usize][input.start.x() as usize] = 'S';
+  grid[input.end.y() as usize][input.end.x() as usize] = 'E';
+
+  let mut s = String::new();
+  for row in grid {
+    s.extend(row);
+    s.push('\n');
+  }
+  s
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::track;
+
+  fn race(input: &Input) -> Option<Race<'_>> {
+    Race::new(input, &track::walk(input))
+  }
+
+  #[test]
+  fn dist_maps() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let from_start = DistMap::new(&input, input.start);
+    let to_end = DistMap::new(&input, input.end);
+    assert_eq!(from_start.get(input.end), Some(84));
+    assert_eq!(to_end.get(input.start), Some(84));
+    assert_eq!(from_start.get(V(0, 0)), None);
+    assert_eq!(from_start.get(V(-1, 3)), None);
+
+    let track = track::walk(&input).unwrap();
+    let along = DistMap::along(&input, track.iter().copied());
+    for y in 0..input.height {
+      for x in 0..input.width {
+        assert_eq!(along.get(V(x, y)), from_start.get(V(x, y)));
+      }
+    }
+  }
+
+  #[test]
+  fn walk_matches_search() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let walked = race(&input).unwrap();
+    let searched = Race::search(&input).unwrap();
+    assert_eq!(walked.best, searched.best);
+    for (max_cheat, min_saving) in [(2, 1), (2, 20), (20, 50), (20, 70)] {
+      assert_eq!(
+        walked.find_cheats(max_cheat, min_saving),
+        searched.find_cheats(max_cheat, min_saving)
+      );
+    }
+
+    // A dead-end spur off the track means it can't be walked.
+    let input = Input::parse(
+      &crate::input::SAMPLE.replace("#...#...#...###", "#...#...#....##"),
+    );
+    assert!(track::walk(&input).is_err());
+    assert_eq!(race(&input).unwrap().best, 84);
+
+    // Walling off E leaves no race at all.
+    let input = Input::parse(
+      &crate::input::SAMPLE.replace("###..E#...#...#", "###.#E#...#...#"),
+    );
+    assert!(race(&input).is_none());
+  }
+
+  #[test]
+  fn short_cheats() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let race = race(&input).unwrap();
+    let cheats = race.find_cheats(2, 0);
+    assert!(cheats.iter().all(|c| {
+      let d = c.end - c.start;
+      d.x().abs() + d.y().abs() == 2
+    }));
+    assert_eq!(race.count_cheats(1, i32::MIN), 0);
+  }
+
+  #[test]
+  fn hist() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let cheats = race(&input).unwrap().find_cheats(2, 1);
+    let hist: Vec<(i32, usize)> = histogram(&cheats).into_iter().collect();
+    assert_eq!(
+      hist,
+      vec![
+        (2, 14),
+        (4, 14),
+        (6, 2),
+        (8, 4),
+        (10, 2),
+        (12, 3),
+        (20, 1),
+        (36, 1),
+        (38, 1),
+        (40, 1),
+        (64, 1)
+      ]
+    );
+  }
+
+  #[test]
+  fn render_cheat() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let cheats = race(&input).unwrap().find_cheats(2, 64);
+    assert_eq!(
+      cheats,
+      vec![Cheat {
+        start: V(7, 7),
+        end: V(5, 7),
+        saving: 64
+      }]
+    );
+    let cheats = race(&input).unwrap().find_cheats(2, 12);
+    let cheat = cheats.iter().find(|c| c.saving == 12).unwrap();
+    let map = render(&input, cheat);
+    let rows: Vec<&str> = map.lines().collect();
+    assert_eq!(rows[1], "#...#...12....#");
+
+    // Ending on E keeps the E.
+    let race = race(&input).unwrap();
+    let cheat = race.cheat(V(7, 7), input.end).unwrap();
+    assert_eq!(cheat.saving, 64);
+    let map = render(&input, &cheat);
+    let rows: Vec<&str> = map.lines().collect();
+    assert_eq!(rows[7], "###..E1...#...#");
+  }
+
+  #[test]
+  fn lookup() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let race = race(&input).unwrap();
+    for cheat in race.find_cheats(20, 50) {
+      assert_eq!(race.cheat(cheat.start, cheat.end), Some(cheat));
+    }
+    assert_eq!(race.cheat(V(1, 3), V(1, 2)), None);
+    assert_eq!(race.cheat(V(1, 3), V(0, 0)), None);
+  }
+}
@@ -0,0 +1,136 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct V(pub i32, pub i32);
+
+impl V {
+  pub fn x(&self) -> i32 {
+    self.0
+  }
+  pub fn y(&self) -> i32 {
+    self.1
+  }
+}
+
+impl std::str::FromStr for V {
+  type Err = String;
+
+  // Parses "x,y".
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (x, y) = s
+      .trim()
+      .split_once(',')
+      .ok_or_else(|| format!("expected x,y but got {:?}", s))?;
+    match (x.trim().parse(), y.trim().parse()) {
+      (Ok(x), Ok(y)) => Ok(V(x, y)),
+      _ => Err(format!("invalid coordinate {:?}", s)),
+    }
+  }
+}
+
+impl std::ops::Add for V {
+  type Output = V;
+
+  fn add(self, rhs: Self) -> Self::Output {
+    V(self.x() + rhs.x(), self.y() + rhs.y())
+  }
+}
+
+impl std::ops::Sub for V {
+  type Output = V;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    V(self.x() - rhs.x(), self.y() - rhs.y())
+  }
+}
+
+pub struct Input {
+  pub width: i32,
+  pub height: i32,
+  pub start: V,
+  pub end: V,
+  pub rows: Vec<Vec<bool>>,
+}
+
+impl Input {
+  pub fn parse(s: &str) -> Input {
+    let mut start: Option<V> = None;
+    let mut end: Option<V> = None;
+    let mut rows: Vec<Vec<bool>> = Vec::new();
+    for l in s.lines() {
+      let mut row = if rows.is_empty() {
+        Vec::new()
+      } else {
+        Vec::with_capacity(rows[0].len())
+      };
+      for (i, c) in l.trim().chars().enumerate() {
+        row.push(match c {
+          '#' => false,
+          '.' => true,
+          'S' => {
+            assert!(start.is_none());
+            start = Some(V(i as i32, rows.len() as i32));
+            true
+          }
+          'E' => {
+            assert!(end.is_none());
+            end = Some(V(i as i32, rows.len() as i32));
+            true
+          }
+          _ => panic!("invalid map character"),
+        });
+      }
+      rows.push(row);
+    }
+
+    Input {
+      width: rows[0].len() as i32,
+      height: rows.len() as i32,
+      start: start.unwrap(),
+      end: end.unwrap(),
+      rows,
+    }
+  }
+
+  pub fn is_within_bounds(&self, pos: V) -> bool {
+    pos.x() >= 0
+      && pos.x() < self.width
+      && pos.y() >= 0
+      && pos.y() < self.height
+  }
+
+  pub fn is_track(&self, pos: V) -> bool {
+    self.is_within_bounds(pos) && self.rows[pos.y() as usize][pos.x() as usize]
+  }
+}
+
+#[cfg(test)]
+pub const SAMPLE: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+";
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parse() {
+    let input = Input::parse(SAMPLE);
+    assert_eq!((input.width, input.height), (15, 15));
+    assert_eq!(input.start, V(1, 3));
+    assert_eq!(input.end, V(5, 7));
+    assert!(input.is_track(V(1, 1)));
+    assert!(!input.is_track(V(0, 1)));
+  }
+}
@@ -1,5 +1,94 @@
+mod cheat;
+mod input;
 mod part1;
+mod part2;
+mod track;
+
+use cheat::{Cheat, Race};
+use input::{Input, V};
+
+const USAGE: &str = "usage: day20 <filename> [min-saving] [--histogram] \
+  [--render <x,y>:<x,y>]";
 
 fn main() {
-  println!("Hello, world!");
+  let mut filename: Option<String> = None;
+  let mut min_saving: Option<i32> = None;
+  let mut show_histogram = false;
+  let mut render: Option<(V, V)> = None;
+
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--histogram" => show_histogram = true,
+      "--render" => {
+        let value = args
+          .next()
+          .unwrap_or_else(|| panic!("--render needs a value\n{}", USAGE));
+        render = Some(parse_cheat(&value).unwrap());
+      }
+      _ if filename.is_none() => filename = Some(arg),
+      _ if min_saving.is_none() => min_saving = Some(arg.parse().unwrap()),
+      _ => panic!("unexpected argument {:?}\n{}", arg, USAGE),
+    }
+  }
+  let filename = filename.unwrap_or_else(|| panic!("{}", USAGE));
+  let min_saving = min_saving.unwrap_or(100);
+
+  let filedata = std::fs::read_to_string(filename).unwrap();
+  let input = Input::parse(&filedata);
+  let walk = track::walk(&input);
+  if let Err(issues) = &walk {
+    eprintln!("Track is not a single path, falling back to search:");
+    for issue in issues {
+      eprintln!("  {}", issue);
+    }
+  }
+  let Some(race) = Race::new(&input, &walk) else {
+    eprintln!("E can't be reached from S");
+    std::process::exit(1);
+  };
+  println!("part1={}", part1::eval(&race, min_saving));
+  println!("part2={}", part2::eval(&race, min_saving));
+
+  if show_histogram {
+    print_histogram("part1", &input, &part1::cheats(&race, min_saving));
+    print_histogram("part2", &input, &part2::cheats(&race, min_saving));
+  }
+
+  if let Some((start, end)) = render {
+    let Some(cheat) = race.cheat(start, end) else {
+      eprintln!(
+        "No cheat from {},{} to {},{}: both must be on the track and at \
+         least 2 apart",
+        start.x(),
+        start.y(),
+        end.x(),
+        end.y()
+      );
+      std::process::exit(1);
+    };
+    println!();
+    println!("Cheat saving {} picoseconds:", cheat.saving);
+    print!("{}", cheat::render(&input, &cheat));
+  }
+}
+
+// Parses "x,y:x,y", the start and end of a cheat.
+fn parse_cheat(s: &str) -> Result<(V, V), String> {
+  let (start, end) = s
+    .split_once(':')
+    .ok_or_else(|| format!("expected <x,y>:<x,y> but got {:?}", s))?;
+  Ok((start.parse()?, end.parse()?))
+}
+
+fn print_histogram(name: &str, input: &Input, cheats: &[Cheat]) {
+  println!();
+  println!("{}:", name);
+  for (saving, count) in cheat::histogram(cheats) {
+    println!("  {} cheats save {} picoseconds", count, saving);
+  }
+  if let Some(best) = cheats.iter().max_by_key(|c| c.saving) {
+    println!("Best cheat:");
+    print!("{}", cheat::render(input, best));
+  }
 }
@@ -0,0 +1,11 @@
+use crate::cheat::{Cheat, Race};
+
+const MAX_CHEAT: i32 = 20;
+
+pub fn eval(race: &Race, min_saving: i32) -> usize {
+  race.count_cheats(MAX_CHEAT, min_saving)
+}
+
+pub fn cheats(race: &Race, min_saving: i32) -> Vec<Cheat> {
+  race.find_cheats(MAX_CHEAT, min_saving)
+}
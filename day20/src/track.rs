@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::input::{Input, V};
+
+const DIRS: [V; 4] = [V(0, -1), V(0, 1), V(-1, 0), V(1, 0)];
+
+/// A way in which the track fails to be a single corridor from S to E.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackIssue {
+  /// More neighbouring track positions than a corridor allows (more than one
+  /// for S and E, more than two for anything else).
+  Branch(V),
+  /// Fewer neighbouring track positions than a corridor needs.
+  DeadEnd(V),
+  /// Track that the walk from S to E never reaches. Reported once per
+  /// connected piece.
+  Disconnected(V),
+}
+
+impl fmt::Display for TrackIssue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TrackIssue::Branch(p) => write!(f, "branch at {},{}", p.x(), p.y()),
+      TrackIssue::DeadEnd(p) => write!(f, "dead end at {},{}", p.x(), p.y()),
+      TrackIssue::Disconnected(p) => {
+        write!(f, "track not connected to S and E at {},{}", p.x(), p.y())
+      }
+    }
+  }
+}
+
+/// Checks that the track is a simple path from S to E and returns its
+/// positions in race order, S first and E last.
+pub fn walk(input: &Input) -> Result<Vec<V>, Vec<TrackIssue>> {
+  let mut issues = Vec::new();
+  for y in 0..input.height {
+    for x in 0..input.width {
+      let pos = V(x, y);
+      if !input.is_track(pos) {
+        continue;
+      }
+      let expected = if pos == input.start || pos == input.end {
+        1
+      } else {
+        2
+      };
+      let n = neighbours(input, pos).count();
+      if n > expected {
+        issues.push(TrackIssue::Branch(pos));
+      } else if n < expected {
+        issues.push(TrackIssue::DeadEnd(pos));
+      }
+    }
+  }
+  if !issues.is_empty() {
+    return Err(issues);
+  }
+
+  // Every position has exactly the right number of neighbours, so the walk
+  // from S can only end at E.
+  let mut track = vec![input.start];
+  let mut prev = input.start;
+  let mut pos = input.start;
+  while pos != input.end {
+    let next = neighbours(input, pos).find(|&n| n != prev).unwrap();
+    prev = pos;
+    pos = next;
+    track.push(pos);
+  }
+
+  // Whatever the walk missed must be loops on their own.
+  let mut seen: HashSet<V> = track.iter().copied().collect();
+  for y in 0..input.height {
+    for x in 0..input.width {
+      let pos = V(x, y);
+      if input.is_track(pos) && !seen.contains(&pos) {
+        issues.push(TrackIssue::Disconnected(pos));
+        mark_connected(input, pos, &mut seen);
+      }
+    }
+  }
+  if !issues.is_empty() {
+    return Err(issues);
+  }
+
+  Ok(track)
+}
+
+fn neighbours(input: &Input, pos: V) -> impl Iterator<Item = V> + '_ {
+  DIRS
+    .into_iter()
+    .map(move |d| pos + d)
+    .filter(|&p| input.is_track(p))
+}
+
+fn mark_connected(input: &Input, from: V, seen: &mut HashSet<V>) {
+  let mut stack = vec![from];
+  seen.insert(from);
+  while let Some(pos) = stack.pop() {
+    for next in neighbours(input, pos) {
+      if seen.insert(next) {
+        stack.push(next);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn single_path() {
+    let input = Input::parse(crate::input::SAMPLE);
+    let track = walk(&input).unwrap();
+    assert_eq!(track.len(), 85);
+    assert_eq!(track[0], input.start);
+    assert_eq!(track[1], V(1, 2));
+    assert_eq!(track[84], input.end);
+  }
+
+  #[test]
+  fn issues() {
+    let input = Input::parse(
+      "#######
+#S...E#
+#.#.###
+#.....#
+#######",
+    );
+    assert_eq!(
+      walk(&input),
+      Err(vec![
+        TrackIssue::Branch(V(1, 1)),
+        TrackIssue::Branch(V(3, 1)),
+        TrackIssue::Branch(V(3, 3)),
+        TrackIssue::DeadEnd(V(5, 3)),
+      ])
+    );
+
+    let input = Input::parse(
+      "#######
+#S...E#
+#######
+#.....#
+#.###.#
+#.....#
+#######",
+    );
+    assert_eq!(walk(&input), Err(vec![TrackIssue::Disconnected(V(1, 3))]));
+  }
+}